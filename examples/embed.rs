@@ -0,0 +1,43 @@
+// Generate the dictionary baked into the crate by the `embedded` feature.
+//
+//     cargo run --example embed -- [path/to/cmudict.dict]
+//
+// Without a path the dictionary is downloaded from the upstream URL.
+
+extern crate flate2;
+extern crate ttaw;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::env;
+use std::fs;
+use std::io::Write;
+use ttaw::cmu::CmuDict;
+
+const OUTPUT: &str = "data/cmudict.bin.gz";
+
+#[cfg(feature = "reqwest")]
+fn download() -> Result<CmuDict, ttaw::Error> {
+    CmuDict::from_source(&ttaw::source::HttpSource::default())
+}
+
+#[cfg(not(feature = "reqwest"))]
+fn download() -> Result<CmuDict, ttaw::Error> {
+    Err(ttaw::Error::InputError(
+        "pass the path to a cmudict.dict, downloading needs the reqwest feature".to_string(),
+    ))
+}
+
+fn main() -> Result<(), ttaw::Error> {
+    let cmudict = match env::args().nth(1) {
+        Some(path) => CmuDict::from_dict_file(&path)?,
+        None => download()?,
+    };
+
+    let mut encoder = GzEncoder::new(vec![], Compression::best());
+    encoder.write_all(&cmudict.to_compact()?)?;
+
+    fs::write(OUTPUT, encoder.finish()?)?;
+    println!("wrote {}", OUTPUT);
+    Ok(())
+}
@@ -0,0 +1,1157 @@
+use encoder::{self, PhoneticEncoder};
+use error::Error;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::iter::Peekable;
+use std::mem;
+use std::path::Path;
+use std::str::Chars;
+
+/// Most phonetic tokens kept for a name, the alternatives multiply quickly.
+const MAX_PHONEMES: usize = 20;
+
+/// Which family of names the rules are tuned for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NameType {
+    /// Names of any origin.
+    #[default]
+    Generic,
+    /// Ashkenazi Jewish names.
+    Ashkenazi,
+    /// Sephardic Jewish names.
+    Sephardic,
+}
+
+/// How close the tokens of two names have to be for them to match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RuleType {
+    /// Sounds that are easily confused, like voiced and voiceless consonants
+    /// at the end of a name, share a token.
+    #[default]
+    Approx,
+    /// Tokens keep every distinction the language rules make.
+    Exact,
+}
+
+impl NameType {
+    // The prefix of the rule files for this name type.
+    fn file_prefix(self) -> &'static str {
+        match self {
+            NameType::Generic => "gen",
+            NameType::Ashkenazi => "ash",
+            NameType::Sephardic => "sep",
+        }
+    }
+
+    // Words in front of a surname that are encoded with and without it.
+    fn name_prefixes(self) -> &'static [&'static str] {
+        match self {
+            NameType::Generic => &[
+                "da", "dal", "de", "del", "dela", "de la", "della", "des", "di", "do", "dos", "du",
+                "van", "von",
+            ],
+            NameType::Ashkenazi => &["bar", "ben", "da", "de", "van", "von"],
+            NameType::Sephardic => &[
+                "al", "el", "da", "dal", "de", "del", "dela", "de la", "della", "des", "di", "do",
+                "dos", "du", "van", "von",
+            ],
+        }
+    }
+}
+
+impl RuleType {
+    fn file_name(self) -> &'static str {
+        match self {
+            RuleType::Approx => "approx",
+            RuleType::Exact => "exact",
+        }
+    }
+}
+
+/// Beider–Morse Phonetic Matching. The language of a name is guessed from its
+/// spelling, then rules for that language (or for any of the candidates when
+/// it's ambiguous) turn it into phonetic tokens, one per plausible
+/// pronunciation. Names match when they share a token.
+///
+/// The rules are read from text files in the layout of the reference
+/// implementation, Apache Commons Codec: `{gen,ash,sep}_languages.txt`,
+/// `_lang.txt` for guessing the language, `_rules_{language}.txt` and the
+/// final `_{approx,exact}_{common,language}.txt` rules. `new` uses its
+/// tables, bundled with the crate under the Apache License 2.0, so tokens
+/// agree with it for single words; see `encode` for names of several.
+///
+/// Rule contexts and language patterns are regular expressions made of
+/// letters, `.`, `[...]` and `[^...]` classes, `(...)` groups, `|` and the
+/// `^` and `$` anchors. Like the reference implementation, contexts without
+/// a class are compared literally, and a context that is a single class
+/// only checks the letter next to the pattern.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::beider_morse::{BeiderMorse, NameType, RuleType};
+///
+/// let bm = BeiderMorse::new(NameType::Generic, RuleType::Approx).unwrap();
+/// assert_eq!(
+///     bm.encode("Renault"),
+///     vec!["rinD", "rinDlt", "rina", "rinalt", "rino", "rinolt", "rinu", "rinult"]
+/// );
+/// assert!(bm.matches("Schmidt", "Schmitt"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BeiderMorse {
+    name_type: NameType,
+    rule_type: RuleType,
+    languages: Vec<String>,
+    lang_rules: Vec<LangRule>,
+    /// Rules by language, `any` for names whose language is ambiguous.
+    rules: HashMap<String, Rules>,
+    common: Rules,
+    /// Final rules by language, `any` as above.
+    finals: HashMap<String, Rules>,
+}
+
+// A language guessing rule: when the pattern is found in a name, the name is
+// in one of the languages, or in none of them if `accept` is false.
+#[derive(Debug, Clone)]
+struct LangRule {
+    pattern: Regex,
+    languages: BTreeSet<String>,
+    accept: bool,
+}
+
+// Rules indexed by the first letter of their pattern, in file order.
+#[derive(Debug, Clone, Default)]
+struct Rules {
+    by_letter: HashMap<char, Vec<Rule>>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Vec<char>,
+    left: Context,
+    right: Context,
+    phonemes: Vec<Phoneme>,
+}
+
+// A phonetic token so far and the languages it holds for, `None` for any.
+#[derive(Debug, Clone, PartialEq)]
+struct Phoneme {
+    text: String,
+    languages: Option<BTreeSet<String>>,
+}
+
+// A rule context, matched against the text before a rule's pattern as
+// `left$` or the text after it as `^right`. Like the reference
+// implementation, expressions without a class that are anchored are
+// compared literally, and a single anchored class only checks one letter.
+#[derive(Debug, Clone)]
+enum Context {
+    Anything,
+    Empty,
+    Equals(Vec<char>),
+    StartsWith(Vec<char>),
+    EndsWith(Vec<char>),
+    Class {
+        matching: bool,
+        letters: Vec<char>,
+        start: bool,
+        end: bool,
+    },
+    Regex(Regex),
+}
+
+// A regular expression: alternatives, any of which may match.
+#[derive(Debug, Clone)]
+struct Regex {
+    alternatives: Vec<Vec<Item>>,
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    Start,
+    End,
+    Any,
+    Letter(char),
+    Class { matching: bool, letters: Vec<char> },
+    Group(Vec<Vec<Item>>),
+}
+
+impl BeiderMorse {
+    /// Load the rule tables bundled with the crate.
+    pub fn new(name_type: NameType, rule_type: RuleType) -> Result<BeiderMorse, Error> {
+        BeiderMorse::load(name_type, rule_type, |name| {
+            bundled(name).map(|text| text.to_string())
+        })
+    }
+
+    /// Load rule tables from `.txt` files in `path`, e.g. the complete tables
+    /// of the reference implementation.
+    pub fn from_dir(
+        path: &str,
+        name_type: NameType,
+        rule_type: RuleType,
+    ) -> Result<BeiderMorse, Error> {
+        let dir = Path::new(path);
+        if !dir.is_dir() {
+            return Err(Error::InputError(format!(
+                "{} isn't a directory of Beider–Morse rules",
+                path
+            )));
+        }
+
+        BeiderMorse::load(name_type, rule_type, |name| {
+            fs::read_to_string(dir.join(format!("{}.txt", name))).ok()
+        })
+    }
+
+    fn load<F>(name_type: NameType, rule_type: RuleType, read: F) -> Result<BeiderMorse, Error>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let prefix = name_type.file_prefix();
+        let required = |name: String| {
+            read(&name).ok_or_else(|| {
+                Error::InputError(format!("missing Beider–Morse rule file {}.txt", name))
+            })
+        };
+
+        let languages = strip_comments(&required(format!("{}_languages", prefix))?)
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty() && l != "any")
+            .collect::<Vec<String>>();
+        let lang_rules = parse_lang_rules(&required(format!("{}_lang", prefix))?)?;
+
+        let final_name = format!("{}_{}", prefix, rule_type.file_name());
+        let mut rules = HashMap::new();
+        let mut finals = HashMap::new();
+
+        for language in languages.iter().map(|l| l.as_str()).chain(Some("any")) {
+            let rules_name = format!("{}_rules_{}", prefix, language);
+            let finals_name = format!("{}_{}", final_name, language);
+
+            // only the rules for any language are required, the others fall
+            // back to them
+            if let Some(text) = read(&rules_name) {
+                rules.insert(language.to_string(), parse_rules(&text, &read)?);
+            } else if language == "any" {
+                required(rules_name)?;
+            }
+
+            if let Some(text) = read(&finals_name) {
+                finals.insert(language.to_string(), parse_rules(&text, &read)?);
+            }
+        }
+
+        let common = parse_rules(&required(format!("{}_common", final_name))?, &read)?;
+
+        Ok(BeiderMorse {
+            name_type,
+            rule_type,
+            languages,
+            lang_rules,
+            rules,
+            common,
+            finals,
+        })
+    }
+
+    pub fn name_type(&self) -> NameType {
+        self.name_type
+    }
+
+    pub fn rule_type(&self) -> RuleType {
+        self.rule_type
+    }
+
+    /// The languages the rules know, besides `any`.
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    /// The languages `name` could be in, judging by its spelling, sorted.
+    /// `any` is one of them while the spelling hasn't settled on any
+    /// language, and the only one if the spelling rules every language out.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::beider_morse::{BeiderMorse, NameType, RuleType};
+    ///
+    /// let bm = BeiderMorse::new(NameType::Generic, RuleType::Approx).unwrap();
+    /// assert_eq!(bm.guess_languages("Mickiewicz"), vec!["polish"]);
+    /// assert_eq!(bm.guess_languages("Thompson"), vec!["english", "greeklatin"]);
+    /// ```
+    pub fn guess_languages(&self, name: &str) -> Vec<String> {
+        match self.guess(&name.to_lowercase().chars().collect::<Vec<char>>()) {
+            Some(languages) => languages.into_iter().collect(),
+            None => vec!["any".to_string()],
+        }
+    }
+
+    /// The phonetic tokens for `name`, with no duplicates. Hyphens and spaces
+    /// separate words, each encoded on its own. That's the reference
+    /// implementation with `concat` off: its default encodes the words joined
+    /// into one string, so tokens for a name of several words, such as
+    /// "Renault Judenburg", differ from its default output.
+    ///
+    /// A generic name starting with "d'" or a prefix like "van" is encoded
+    /// both with and without it, Ashkenazi and Sephardic names drop prefixes
+    /// like "ben" or "de", and Sephardic names drop elided articles like "d'"
+    /// and "l'" too. Empty if `name` has no letters the rules code.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::beider_morse::{BeiderMorse, NameType, RuleType};
+    ///
+    /// let bm = BeiderMorse::new(NameType::Generic, RuleType::Exact).unwrap();
+    /// assert_eq!(
+    ///     bm.encode("d'Ortley"),
+    ///     vec!["ortlaj", "ortlej", "dortlaj", "dortlej"]
+    /// );
+    /// ```
+    pub fn encode(&self, name: &str) -> Vec<String> {
+        let input = name.to_lowercase().replace('-', " ");
+        let input = input.trim();
+
+        if self.name_type == NameType::Generic {
+            if let Some(rest) = input.strip_prefix("d'") {
+                return self.encode_all(&[rest, &format!("d{}", rest)]);
+            }
+
+            for prefix in self.name_type.name_prefixes() {
+                if let Some(rest) = input.strip_prefix(&format!("{} ", prefix)) {
+                    return self.encode_all(&[rest, &format!("{}{}", prefix, rest)]);
+                }
+            }
+        }
+
+        let mut words = input
+            .split_whitespace()
+            .map(|w| match self.name_type {
+                NameType::Sephardic => w.rsplit('\'').next().unwrap_or(w),
+                _ => w,
+            })
+            .collect::<Vec<&str>>();
+        if self.name_type != NameType::Generic {
+            let prefixes = self.name_type.name_prefixes();
+            words.retain(|w| !prefixes.contains(w));
+        }
+
+        match words.as_slice() {
+            // nothing but prefixes, so they're the name
+            [] => self.encode_word(input),
+            [word] => self.encode_word(word),
+            words => self.encode_all(words),
+        }
+    }
+
+    /// Whether two names share a phonetic token.
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        let b = self.encode(b);
+        self.encode(a).iter().any(|token| b.contains(token))
+    }
+
+    // The tokens of each name, in order, without duplicates.
+    fn encode_all(&self, names: &[&str]) -> Vec<String> {
+        let mut tokens: Vec<String> = vec![];
+        for name in names {
+            for token in self.encode(name) {
+                if !tokens.contains(&token) {
+                    tokens.push(token);
+                }
+            }
+        }
+
+        tokens
+    }
+
+    fn encode_word(&self, word: &str) -> Vec<String> {
+        let word = word.chars().collect::<Vec<char>>();
+        let languages = self.guess(&word);
+        let language = match languages {
+            Some(ref set) if set.len() == 1 => set.iter().next().map(|l| l.as_str()),
+            _ => None,
+        };
+
+        let rules = language
+            .and_then(|l| self.rules.get(l))
+            .or_else(|| self.rules.get("any"));
+        let finals = language
+            .and_then(|l| self.finals.get(l))
+            .or_else(|| self.finals.get("any"));
+
+        let mut phonemes = vec![Phoneme {
+            text: String::new(),
+            languages,
+        }];
+
+        let mut i = 0;
+        while i < word.len() {
+            match rules.and_then(|r| r.find(&word, i)) {
+                Some(rule) => {
+                    phonemes = apply(&phonemes, &rule.phonemes);
+                    i += rule.pattern.len();
+                }
+                // letters no rule codes are skipped
+                None => i += 1,
+            }
+        }
+
+        phonemes = apply_final(phonemes, &self.common);
+        if let Some(finals) = finals {
+            phonemes = apply_final(phonemes, finals);
+        }
+
+        phonemes
+            .into_iter()
+            .map(|p| p.text)
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+
+    // The languages a lowercased name could be in, `None` for any. `any`
+    // itself starts out as one of them, so only a rule accepting some
+    // languages narrows a name down to a single language.
+    fn guess(&self, name: &[char]) -> Option<BTreeSet<String>> {
+        let mut languages: BTreeSet<String> = self.languages.iter().cloned().collect();
+        languages.insert("any".to_string());
+
+        for rule in &self.lang_rules {
+            if rule.pattern.find(name) {
+                if rule.accept {
+                    languages = languages.intersection(&rule.languages).cloned().collect();
+                } else {
+                    languages = languages.difference(&rule.languages).cloned().collect();
+                }
+            }
+        }
+
+        if languages.is_empty() {
+            None
+        } else {
+            Some(languages)
+        }
+    }
+}
+
+impl PhoneticEncoder for BeiderMorse {
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        let tokens = BeiderMorse::encode(self, w);
+        Ok(if tokens.is_empty() {
+            None
+        } else {
+            Some(tokens)
+        })
+    }
+
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        let (a, b) = (BeiderMorse::encode(self, a), BeiderMorse::encode(self, b));
+        Ok(a.iter()
+            .any(|a| b.iter().any(|b| encoder::codes_rhyme(a, b))))
+    }
+
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error> {
+        let (a, b) = (BeiderMorse::encode(self, a), BeiderMorse::encode(self, b));
+        Ok(a.iter()
+            .any(|a| b.iter().any(|b| encoder::codes_alliterate(a, b))))
+    }
+}
+
+impl Rules {
+    // The first rule matching `text` at `i`.
+    fn find(&self, text: &[char], i: usize) -> Option<&Rule> {
+        self.by_letter
+            .get(&text[i])?
+            .iter()
+            .find(|rule| rule.matches(text, i))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.by_letter.is_empty()
+    }
+}
+
+impl Rule {
+    fn matches(&self, text: &[char], i: usize) -> bool {
+        let end = i + self.pattern.len();
+        end <= text.len()
+            && text[i..end] == self.pattern[..]
+            && self.right.matches(&text[end..])
+            && self.left.matches(&text[..i])
+    }
+}
+
+// Every phoneme so far followed by every alternative of the next one, keeping
+// only the combinations that hold for some language. Duplicates are merged
+// later by `apply_final`, until then they count towards `MAX_PHONEMES` as
+// they do in the reference implementation.
+fn apply(phonemes: &[Phoneme], next: &[Phoneme]) -> Vec<Phoneme> {
+    let mut applied: Vec<Phoneme> = vec![];
+
+    for left in phonemes {
+        for right in next {
+            let languages = match (&left.languages, &right.languages) {
+                (None, languages) | (languages, None) => languages.clone(),
+                (Some(a), Some(b)) => Some(a.intersection(b).cloned().collect()),
+            };
+
+            if languages.as_ref().is_some_and(|l| l.is_empty()) {
+                continue;
+            }
+
+            applied.push(Phoneme {
+                text: format!("{}{}", left.text, right.text),
+                languages,
+            });
+
+            if applied.len() >= MAX_PHONEMES {
+                return applied;
+            }
+        }
+    }
+
+    applied
+}
+
+// Run final rules over each token's text. Letters no final rule matches are
+// kept as they are, and tokens that come out the same are merged. The tokens
+// come out sorted.
+fn apply_final(phonemes: Vec<Phoneme>, rules: &Rules) -> Vec<Phoneme> {
+    if rules.is_empty() {
+        return phonemes;
+    }
+
+    let mut merged: BTreeMap<String, Option<BTreeSet<String>>> = BTreeMap::new();
+    for phoneme in phonemes {
+        let text = phoneme.text.chars().collect::<Vec<char>>();
+        let mut sub = vec![Phoneme {
+            text: String::new(),
+            languages: phoneme.languages,
+        }];
+
+        let mut i = 0;
+        while i < text.len() {
+            match rules.find(&text, i) {
+                Some(rule) => {
+                    sub = apply(&sub, &rule.phonemes);
+                    i += rule.pattern.len();
+                }
+                None => {
+                    for p in &mut sub {
+                        p.text.push(text[i]);
+                    }
+                    i += 1;
+                }
+            }
+        }
+
+        for p in sub {
+            match merged.get_mut(&p.text) {
+                Some(languages) => {
+                    *languages = match (languages.take(), p.languages) {
+                        (Some(a), Some(b)) => Some(a.union(&b).cloned().collect()),
+                        _ => None,
+                    }
+                }
+                None => {
+                    merged.insert(p.text, p.languages);
+                }
+            }
+        }
+    }
+
+    merged
+        .into_iter()
+        .map(|(text, languages)| Phoneme { text, languages })
+        .collect()
+}
+
+impl Context {
+    fn matches(&self, text: &[char]) -> bool {
+        match self {
+            Context::Anything => true,
+            Context::Empty => text.is_empty(),
+            Context::Equals(letters) => text == &letters[..],
+            Context::StartsWith(letters) => text.starts_with(letters),
+            Context::EndsWith(letters) => text.ends_with(letters),
+            Context::Class {
+                matching,
+                letters,
+                start,
+                end,
+            } => {
+                let letter = match (start, end) {
+                    (true, true) if text.len() == 1 => text.first(),
+                    (true, true) => None,
+                    (true, false) => text.first(),
+                    _ => text.last(),
+                };
+                letter.is_some_and(|c| letters.contains(c) == *matching)
+            }
+            Context::Regex(regex) => regex.find(text),
+        }
+    }
+
+    fn parse(regex: &str) -> Result<Context, Error> {
+        let start = regex.starts_with('^');
+        let end = regex.len() > usize::from(start) && regex.ends_with('$');
+        let content = &regex[usize::from(start)..regex.len() - usize::from(end)];
+
+        if !content.contains('[') {
+            match (start, end) {
+                (true, true) if content.is_empty() => return Ok(Context::Empty),
+                (true, true) => return Ok(Context::Equals(content.chars().collect())),
+                (true, false) | (false, true) if content.is_empty() => {
+                    return Ok(Context::Anything)
+                }
+                (true, false) => return Ok(Context::StartsWith(content.chars().collect())),
+                (false, true) => return Ok(Context::EndsWith(content.chars().collect())),
+                (false, false) => (),
+            }
+        } else if (start || end) && content.starts_with('[') && content.ends_with(']') {
+            let class = &content[1..content.len() - 1];
+            if !class.contains('[') {
+                let (matching, letters) = match class.strip_prefix('^') {
+                    Some(letters) => (false, letters),
+                    None => (true, class),
+                };
+                return Ok(Context::Class {
+                    matching,
+                    letters: letters.chars().collect(),
+                    start,
+                    end,
+                });
+            }
+        }
+
+        Ok(Context::Regex(Regex::parse(regex)?))
+    }
+}
+
+impl Regex {
+    // Whether the expression matches anywhere in the text.
+    fn find(&self, text: &[char]) -> bool {
+        (0..=text.len()).any(|start| {
+            let mut ends = vec![];
+            for alternative in &self.alternatives {
+                match_items(alternative, text, start, &mut ends);
+            }
+            !ends.is_empty()
+        })
+    }
+
+    fn parse(regex: &str) -> Result<Regex, Error> {
+        let mut chars = regex.chars().peekable();
+        match parse_alternatives(&mut chars, false) {
+            Some(alternatives) => Ok(Regex { alternatives }),
+            None => Err(Error::InputError(format!(
+                "unsupported Beider–Morse pattern '{}', expected letters, classes, groups, ., |, ^ and $",
+                regex
+            ))),
+        }
+    }
+}
+
+impl Item {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Item::Any => true,
+            Item::Letter(l) => *l == c,
+            Item::Class { matching, letters } => letters.contains(&c) == *matching,
+            Item::Start | Item::End | Item::Group(_) => false,
+        }
+    }
+}
+
+// Collect every position in the text where `items` can stop matching when
+// they start at `pos`.
+fn match_items(items: &[Item], text: &[char], pos: usize, ends: &mut Vec<usize>) {
+    let (item, rest) = match items.split_first() {
+        Some(split) => split,
+        None => {
+            ends.push(pos);
+            return;
+        }
+    };
+
+    let mut next = vec![];
+    match item {
+        Item::Start if pos == 0 => next.push(pos),
+        Item::End if pos == text.len() => next.push(pos),
+        Item::Start | Item::End => (),
+        Item::Group(alternatives) => {
+            for alternative in alternatives {
+                match_items(alternative, text, pos, &mut next);
+            }
+        }
+        item => {
+            if text.get(pos).is_some_and(|c| item.matches(*c)) {
+                next.push(pos + 1);
+            }
+        }
+    }
+
+    for pos in next {
+        match_items(rest, text, pos, ends);
+    }
+}
+
+// The alternatives of an expression, or of a group up to its `)`. `None`
+// for syntax outside what the rule tables use, like repetition.
+fn parse_alternatives(chars: &mut Peekable<Chars>, group: bool) -> Option<Vec<Vec<Item>>> {
+    let mut alternatives = vec![];
+    let mut items = vec![];
+
+    loop {
+        let item = match chars.next() {
+            None if group => return None,
+            Some(')') if group => break,
+            None => break,
+            Some('|') => {
+                alternatives.push(mem::take(&mut items));
+                continue;
+            }
+            Some('(') => Item::Group(parse_alternatives(chars, true)?),
+            Some('[') => {
+                let matching = chars.next_if_eq(&'^').is_none();
+                let mut letters = vec![];
+                loop {
+                    match chars.next()? {
+                        ']' => break,
+                        c => letters.push(c),
+                    }
+                }
+                Item::Class { matching, letters }
+            }
+            Some('^') => Item::Start,
+            Some('$') => Item::End,
+            Some('.') => Item::Any,
+            Some(c) if ")]*+?{}\\".contains(c) => return None,
+            Some(c) => Item::Letter(c),
+        };
+        items.push(item);
+    }
+
+    alternatives.push(items);
+    Some(alternatives)
+}
+
+// Drop `//` line comments and `/* */` block comments.
+fn strip_comments(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_block = false;
+
+    for line in text.lines() {
+        let mut line = line;
+        let mut kept = String::new();
+
+        loop {
+            if in_block {
+                match line.find("*/") {
+                    Some(i) => {
+                        line = &line[i + 2..];
+                        in_block = false;
+                    }
+                    None => break,
+                }
+            } else if let Some(i) = line.find("/*") {
+                kept.push_str(&line[..i]);
+                line = &line[i + 2..];
+                in_block = true;
+            } else {
+                kept.push_str(line);
+                break;
+            }
+        }
+
+        if let Some(i) = kept.find("//") {
+            kept.truncate(i);
+        }
+        stripped.push_str(&kept);
+        stripped.push('\n');
+    }
+
+    stripped
+}
+
+fn parse_lang_rules(text: &str) -> Result<Vec<LangRule>, Error> {
+    let mut rules = vec![];
+
+    for line in strip_comments(text).lines() {
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        match fields.as_slice() {
+            [] => continue,
+            [pattern, languages, accept] => rules.push(LangRule {
+                pattern: Regex::parse(pattern)?,
+                languages: languages.split('+').map(|l| l.to_string()).collect(),
+                accept: *accept == "true",
+            }),
+            _ => {
+                return Err(Error::InputError(format!(
+                    "invalid Beider–Morse language rule '{}'",
+                    line.trim()
+                )))
+            }
+        }
+    }
+
+    Ok(rules)
+}
+
+fn parse_rules<F>(text: &str, read: &F) -> Result<Rules, Error>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut rules = Rules::default();
+
+    for line in strip_comments(text).lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("#include") {
+            let name = name.trim();
+            let included = read(name).ok_or_else(|| {
+                Error::InputError(format!("missing Beider–Morse rule file {}.txt", name))
+            })?;
+            for (letter, mut included) in parse_rules(&included, read)?.by_letter {
+                rules
+                    .by_letter
+                    .entry(letter)
+                    .or_default()
+                    .append(&mut included);
+            }
+            continue;
+        }
+
+        let fields = line
+            .split_whitespace()
+            .map(strip_quotes)
+            .collect::<Vec<&str>>();
+        let rule = match fields.as_slice() {
+            [pattern, left, right, phonemes] if !pattern.is_empty() => Rule {
+                pattern: pattern.chars().collect(),
+                left: Context::parse(&format!("{}$", left))?,
+                right: Context::parse(&format!("^{}", right))?,
+                phonemes: parse_phonemes(phonemes),
+            },
+            _ => {
+                return Err(Error::InputError(format!(
+                    "invalid Beider–Morse rule '{}'",
+                    line
+                )))
+            }
+        };
+
+        rules
+            .by_letter
+            .entry(rule.pattern[0])
+            .or_default()
+            .push(rule);
+    }
+
+    Ok(rules)
+}
+
+// A rule field without the quotes around it.
+fn strip_quotes(field: &str) -> &str {
+    let field = field.strip_prefix('"').unwrap_or(field);
+    field.strip_suffix('"').unwrap_or(field)
+}
+
+// A phoneme expression: `text`, `text[language+language]` or alternatives of
+// those in parentheses, `(a|b[language])`. A `|` at either end of the
+// alternatives adds an empty one.
+fn parse_phonemes(expression: &str) -> Vec<Phoneme> {
+    let body = match expression
+        .strip_prefix('(')
+        .and_then(|e| e.strip_suffix(')'))
+    {
+        Some(body) => body,
+        None => return vec![parse_phoneme(expression)],
+    };
+
+    // like Java's `split`, which the reference implementation uses, trailing
+    // empty alternatives are dropped before the one for `|` is added
+    let mut alternatives = body.split('|').collect::<Vec<&str>>();
+    while alternatives.last() == Some(&"") {
+        alternatives.pop();
+    }
+
+    let mut phonemes = alternatives
+        .into_iter()
+        .map(parse_phoneme)
+        .collect::<Vec<Phoneme>>();
+    if body.is_empty() || body.starts_with('|') || body.ends_with('|') {
+        phonemes.push(parse_phoneme(""));
+    }
+
+    phonemes
+}
+
+fn parse_phoneme(alternative: &str) -> Phoneme {
+    match alternative.find('[') {
+        Some(i) => Phoneme {
+            text: alternative[..i].to_string(),
+            languages: Some(
+                alternative[i + 1..]
+                    .trim_end_matches(']')
+                    .split('+')
+                    .map(|l| l.to_string())
+                    .collect(),
+            ),
+        },
+        None => Phoneme {
+            text: alternative.to_string(),
+            languages: None,
+        },
+    }
+}
+
+// The rule tables bundled with the crate.
+fn bundled(name: &str) -> Option<&'static str> {
+    macro_rules! files {
+        ($($file:expr),* $(,)*) => {
+            match name {
+                $($file => Some(include_str!(concat!("../data/beider_morse/", $file, ".txt"))),)*
+                _ => None,
+            }
+        };
+    }
+
+    files!(
+        "gen_languages",
+        "gen_lang",
+        "gen_rules_any",
+        "gen_rules_arabic",
+        "gen_rules_cyrillic",
+        "gen_rules_czech",
+        "gen_rules_dutch",
+        "gen_rules_english",
+        "gen_rules_french",
+        "gen_rules_german",
+        "gen_rules_greek",
+        "gen_rules_greeklatin",
+        "gen_rules_hebrew",
+        "gen_rules_hungarian",
+        "gen_rules_italian",
+        "gen_rules_polish",
+        "gen_rules_portuguese",
+        "gen_rules_romanian",
+        "gen_rules_russian",
+        "gen_rules_spanish",
+        "gen_rules_turkish",
+        "gen_approx_any",
+        "gen_approx_arabic",
+        "gen_approx_common",
+        "gen_approx_cyrillic",
+        "gen_approx_czech",
+        "gen_approx_dutch",
+        "gen_approx_english",
+        "gen_approx_french",
+        "gen_approx_german",
+        "gen_approx_greek",
+        "gen_approx_greeklatin",
+        "gen_approx_hebrew",
+        "gen_approx_hungarian",
+        "gen_approx_italian",
+        "gen_approx_polish",
+        "gen_approx_portuguese",
+        "gen_approx_romanian",
+        "gen_approx_russian",
+        "gen_approx_spanish",
+        "gen_approx_turkish",
+        "gen_exact_any",
+        "gen_exact_approx_common",
+        "gen_exact_arabic",
+        "gen_exact_common",
+        "gen_exact_cyrillic",
+        "gen_exact_czech",
+        "gen_exact_dutch",
+        "gen_exact_english",
+        "gen_exact_french",
+        "gen_exact_german",
+        "gen_exact_greek",
+        "gen_exact_greeklatin",
+        "gen_exact_hebrew",
+        "gen_exact_hungarian",
+        "gen_exact_italian",
+        "gen_exact_polish",
+        "gen_exact_portuguese",
+        "gen_exact_romanian",
+        "gen_exact_russian",
+        "gen_exact_spanish",
+        "gen_exact_turkish",
+        "ash_languages",
+        "ash_lang",
+        "ash_rules_any",
+        "ash_rules_cyrillic",
+        "ash_rules_english",
+        "ash_rules_french",
+        "ash_rules_german",
+        "ash_rules_hebrew",
+        "ash_rules_hungarian",
+        "ash_rules_polish",
+        "ash_rules_romanian",
+        "ash_rules_russian",
+        "ash_rules_spanish",
+        "ash_approx_any",
+        "ash_approx_common",
+        "ash_approx_cyrillic",
+        "ash_approx_english",
+        "ash_approx_french",
+        "ash_approx_german",
+        "ash_approx_hebrew",
+        "ash_approx_hungarian",
+        "ash_approx_polish",
+        "ash_approx_romanian",
+        "ash_approx_russian",
+        "ash_approx_spanish",
+        "ash_exact_any",
+        "ash_exact_approx_common",
+        "ash_exact_common",
+        "ash_exact_cyrillic",
+        "ash_exact_english",
+        "ash_exact_french",
+        "ash_exact_german",
+        "ash_exact_hebrew",
+        "ash_exact_hungarian",
+        "ash_exact_polish",
+        "ash_exact_romanian",
+        "ash_exact_russian",
+        "ash_exact_spanish",
+        "sep_languages",
+        "sep_lang",
+        "sep_rules_any",
+        "sep_rules_french",
+        "sep_rules_hebrew",
+        "sep_rules_italian",
+        "sep_rules_portuguese",
+        "sep_rules_spanish",
+        "sep_approx_any",
+        "sep_approx_common",
+        "sep_approx_french",
+        "sep_approx_hebrew",
+        "sep_approx_italian",
+        "sep_approx_portuguese",
+        "sep_approx_spanish",
+        "sep_exact_any",
+        "sep_exact_approx_common",
+        "sep_exact_common",
+        "sep_exact_french",
+        "sep_exact_hebrew",
+        "sep_exact_italian",
+        "sep_exact_portuguese",
+        "sep_exact_spanish",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn contexts() {
+        let vowel_next = Context::parse("^[aeiou]").unwrap();
+        assert!(vowel_next.matches(&chars("a")));
+        assert!(!vowel_next.matches(&chars("t")));
+        assert!(!vowel_next.matches(&chars("")));
+
+        assert!(Context::parse("$").unwrap().matches(&chars("ab")));
+        assert!(Context::parse("^$").unwrap().matches(&chars("")));
+        assert!(!Context::parse("^$").unwrap().matches(&chars("ab")));
+        assert!(Context::parse("ab$").unwrap().matches(&chars("cab")));
+        assert!(!Context::parse("^ab").unwrap().matches(&chars("cab")));
+
+        // taken literally, as the reference implementation does
+        assert!(!Context::parse("^(a|o)").unwrap().matches(&chars("a")));
+        assert!(!Context::parse(".$").unwrap().matches(&chars("a")));
+
+        let not_s_before_e = Context::parse("^[^s]e").unwrap();
+        assert!(not_s_before_e.matches(&chars("tea")));
+        assert!(!not_s_before_e.matches(&chars("sea")));
+
+        // `^` on its own matches anything
+        assert!(Context::parse("^|[^s]$").unwrap().matches(&chars("s")));
+    }
+
+    #[test]
+    fn regexes() {
+        let found = |regex, text| Regex::parse(regex).unwrap().find(&chars(text));
+        assert!(found("ski$", "kowalski"));
+        assert!(!found("^ski", "kowalski"));
+        assert!(found("^k(o|a)w", "kowalski"));
+        assert!(found("w(x|a)(l|ls)k", "kowalski"));
+        assert!(!found("w(x|y)", "kowalski"));
+        assert!(found("l.k", "kowalski"));
+        assert!(found("[^aeiou]i$|^x", "kowalski"));
+        assert!(found("^$", ""));
+        assert!(Regex::parse("(a|b").is_err());
+        assert!(Regex::parse("a*").is_err());
+    }
+
+    #[test]
+    fn phoneme_expressions() {
+        assert_eq!(
+            parse_phonemes("(S[german]|sk)"),
+            vec![
+                Phoneme {
+                    text: "S".to_string(),
+                    languages: Some(vec!["german".to_string()].into_iter().collect()),
+                },
+                Phoneme {
+                    text: "sk".to_string(),
+                    languages: None,
+                },
+            ]
+        );
+        assert_eq!(parse_phonemes("")[0].text, "");
+
+        let texts = |expression| {
+            parse_phonemes(expression)
+                .into_iter()
+                .map(|p| p.text)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(texts("(a|)"), vec!["a", ""]);
+        assert_eq!(texts("(|a)"), vec!["", "a", ""]);
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            strip_comments("a // b\n/* c\nd */ e\n")
+                .lines()
+                .collect::<Vec<&str>>(),
+            vec!["a ", "", " e"]
+        );
+    }
+
+    #[test]
+    fn languages_restrict_alternatives() {
+        let german: BTreeSet<String> = vec!["german".to_string()].into_iter().collect();
+        let phonemes = vec![Phoneme {
+            text: "a".to_string(),
+            languages: Some(german),
+        }];
+
+        let applied = apply(&phonemes, &parse_phonemes("(x[german]|tS[english])"));
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].text, "ax");
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let read = |_: &str| None;
+        assert!(parse_rules("\"a\" \"[b\" \"\" \"a\"", &read).is_err());
+        assert!(parse_rules("\"a\" \"\" \"a\"", &read).is_err());
+        assert!(parse_rules("#include missing", &read).is_err());
+        assert!(parse_lang_rules("sch german").is_err());
+        assert!(parse_lang_rules("sch.* german true").is_err());
+    }
+}
@@ -1,32 +1,674 @@
 extern crate pest;
 
+use compact::{self, CompactDict};
+use distance;
 use error::Error;
-use metaphone::{Rule, Word};
-use pest::Parser;
-#[cfg(feature = "reqwest")]
-use reqwest;
+#[cfg(feature = "embedded")]
+use flate2::read::GzDecoder;
+use g2p;
+use index::{syllable_count, HomophoneIndex, RhymeIndex, StressIndex};
+use ipa;
+use phoneme::{Phone, Pronunciation, Stress};
+use pronouncing::PronouncingDict;
+use rhyme::{self, RhymeKind};
 use serde_json;
-use std::collections::HashMap;
-use std::fs;
+use sha2::{Digest, Sha256};
+use source::DictionarySource;
 #[cfg(feature = "reqwest")]
-use std::io::{self, BufRead};
-use std::path::Path;
+use source::HttpSource;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+#[cfg(feature = "embedded")]
+use std::io::Read;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use syllable::{syllabify, Syllable};
+
+/// The dictionary in the compact binary format, gzipped. Generated from the
+/// cmusphinx cmudict.dict with SHA-256
+/// 59d6398f55297e59afb2ca3276380827524c0940fcbbfcd19022bb76fd55f719,
+/// regenerate it with `cargo run --example embed`.
+#[cfg(feature = "embedded")]
+static EMBEDDED: &[u8] = include_bytes!("../data/cmudict.bin.gz");
 
 pub struct CmuDict {
-    dict: HashMap<String, Vec<Vec<String>>>,
+    dict: Entries,
+    /// Entries added, replaced (`Some`) or removed (`None`) on top of `dict`,
+    /// consulted first.
+    overlay: HashMap<String, Option<Vec<Vec<String>>>>,
+    annotations: Annotations,
+    metadata: Option<DictMetadata>,
+    rhyme_index: OnceLock<RhymeIndex>,
+    homophone_index: OnceLock<HomophoneIndex>,
+    unstressed_homophone_index: OnceLock<HomophoneIndex>,
+    stress_index: OnceLock<StressIndex>,
+}
+
+/// Narrow down the words returned by `CmuDict::rhymes_for_with`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RhymeOptions {
+    /// Only words with this many syllables.
+    pub syllables: Option<usize>,
+    /// Rhyme on every pronunciation of the word and of the candidates, rather
+    /// than just the first of each.
+    pub all_variants: bool,
+    /// Predict the pronunciation of words that aren't in the dictionary
+    /// rather than treating them as rhyming with nothing.
+    pub predict: bool,
+    /// Skip pronunciations annotated with any of these, e.g. `foreign` or
+    /// `abbrev`, see `CmuDict::annotations`.
+    pub exclude: Vec<String>,
+}
+
+/// Widen the words returned by `CmuDict::homophones_with`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HomophoneOptions {
+    /// Words whose pronunciations differ only in stress count too, e.g.
+    /// "insight" and "incite".
+    pub ignore_stress: bool,
+}
+
+/// How `CmuDict::alliteration_with` decides whether two words alliterate.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlliterationOptions {
+    /// Match the whole consonant cluster the words start with, so "snappy"
+    /// alliterates with "snails" but not "sails", rather than just the first
+    /// consonant.
+    pub clusters: bool,
+    /// Let words starting with the same vowel sound alliterate, e.g. "apple"
+    /// and "actor".
+    pub vowels: bool,
+    /// Predict the pronunciation of words that aren't in the dictionary
+    /// rather than treating them as alliterating with nothing.
+    pub predict: bool,
+}
+
+/// The words `CmuDict::query` looks for. A word matches when one of its
+/// pronunciations meets every criterion that's set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    /// Only words with this many syllables.
+    pub syllables: Option<usize>,
+    /// The stress of each syllable, `0`, `1` or `2` as in
+    /// `CmuDict::stress_pattern`, with `?` for any one syllable and `*` for any
+    /// number of them, e.g. `"?1?"` or `"*10"`.
+    pub stress: Option<String>,
+    /// Only words rhyming with this one.
+    pub rhymes_with: Option<String>,
+    /// Phones the word starts with. Vowels without a stress digit match any
+    /// stress.
+    pub starts_with: Vec<String>,
+    /// Phones the word ends with, stress digits as for `starts_with`.
+    pub ends_with: Vec<String>,
+}
+
+/// A word's pronunciations, as returned by `CmuDict::encoding_or_predict`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Encoding {
+    pub pronunciations: Vec<Vec<String>>,
+    /// The word isn't in the dictionary, its pronunciation was predicted from
+    /// its spelling.
+    pub predicted: bool,
+}
+
+/// Every word's pronunciations, as parsed from dictionary text.
+pub(crate) type Dict = HashMap<String, Vec<Vec<String>>>;
+
+/// The `# ...` comments on a word's pronunciations, one per pronunciation. Only
+/// words with at least one are kept.
+pub(crate) type Annotations = HashMap<String, Vec<Option<String>>>;
+
+// How an overlay line changes a word.
+#[derive(Clone, Copy, PartialEq)]
+enum Edit {
+    Replace,
+    Add,
+    Remove,
+}
+
+// The words and their pronunciations, either deserialized into a map or looked
+// up in place in the compact format.
+enum Entries {
+    Map(HashMap<String, Vec<Vec<String>>>),
+    Compact(CompactDict),
+}
+
+type EntryIter<'a> = Box<dyn Iterator<Item = (&'a str, Cow<'a, [Vec<String>]>)> + 'a>;
+
+impl Entries {
+    fn get(&self, w: &str) -> Option<Cow<'_, [Vec<String>]>> {
+        match self {
+            Entries::Map(m) => m.get(w).map(|v| Cow::Borrowed(v.as_slice())),
+            Entries::Compact(c) => c.get(w).map(Cow::Owned),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Entries::Map(m) => m.len(),
+            Entries::Compact(c) => c.len(),
+        }
+    }
+
+    fn iter(&self) -> EntryIter<'_> {
+        match self {
+            Entries::Map(m) => Box::new(
+                m.iter()
+                    .map(|(k, v)| (k.as_str(), Cow::Borrowed(v.as_slice()))),
+            ),
+            Entries::Compact(c) => Box::new(c.iter().map(|(k, v)| (k, Cow::Owned(v)))),
+        }
+    }
+}
+
+/// Provenance of a serialized dictionary. It's written to a sidecar next to the
+/// dictionary (`cmudict.json.meta` for `cmudict.json`) and checked every time
+/// the dictionary is loaded, a mismatch is an `Error::IntegrityError`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DictMetadata {
+    /// Where the dictionary was fetched from, if the source says.
+    pub source: Option<String>,
+    /// SHA-256 of the dictionary text as it was fetched, pins the upstream
+    /// revision. `None` for dictionaries that weren't fetched from a source,
+    /// like subsets written by `CmuDict::write_json` from a dictionary file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_sha256: Option<String>,
+    /// Number of words in the dictionary.
+    pub entries: usize,
+    /// SHA-256 of the serialized dictionary.
+    pub sha256: String,
+    /// The `# ...` comments on the pronunciations that had them, e.g.
+    /// `foreign`, which the serialized dictionary doesn't carry.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub annotations: HashMap<String, Vec<Option<String>>>,
 }
 
 impl CmuDict {
+    fn with_entries(dict: Entries, metadata: Option<DictMetadata>) -> CmuDict {
+        CmuDict {
+            dict,
+            overlay: HashMap::new(),
+            annotations: metadata
+                .as_ref()
+                .map(|m| m.annotations.clone())
+                .unwrap_or_default(),
+            metadata,
+            rhyme_index: OnceLock::new(),
+            homophone_index: OnceLock::new(),
+            unstressed_homophone_index: OnceLock::new(),
+            stress_index: OnceLock::new(),
+        }
+    }
+
     /// Initialize the CmuDict with a path to the existing serialized CMU dictionary
     /// or a directoy containing it. If the dictionary doesn't exisit, it will be
     /// downloaded and serialized at the location specified by the path parameter.
     pub fn new(path: &str) -> Result<CmuDict, Error> {
-        match from_json_file(&Path::new(path)) {
-            Ok(d) => Ok(CmuDict { dict: d }),
-            Err(e) => Err(e),
+        from_json_file(Path::new(path), default_source().as_deref())
+    }
+
+    /// Like `new`, but when the serialized dictionary doesn't exist it's fetched
+    /// from `source` instead of the upstream URL before being serialized at the
+    /// location specified by the path parameter.
+    ///
+    /// ```rust
+    /// extern crate tempfile;
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// use ttaw::source::FileSource;
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("cmudict.json");
+    /// let source = FileSource::new("tests/fixtures/cmudict.dict");
+    /// let cmudict = CmuDict::with_source(path.to_str().unwrap(), &source).unwrap();
+    /// assert!(cmudict.rhyme("far", "tar").unwrap());
+    /// assert!(path.exists());
+    /// ```
+    pub fn with_source<S: DictionarySource>(path: &str, source: &S) -> Result<CmuDict, Error> {
+        from_json_file(Path::new(path), Some(source))
+    }
+
+    /// Initialize the CmuDict straight from `source` without serializing it
+    /// anywhere.
+    pub fn from_source<S: DictionarySource + ?Sized>(source: &S) -> Result<CmuDict, Error> {
+        CmuDict::from_dict_reader(io::Cursor::new(source.fetch()?))
+    }
+
+    /// Initialize the CmuDict from text in the upstream cmudict.dict format,
+    /// one word per line followed by its phones. Alternate pronunciations
+    /// marked like `word(2)` are collected under `word`. Nothing is
+    /// downloaded or written to disk.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let dict = "far F AA1 R\ntar T AA1 R\nread R EH1 D\nread(2) R IY1 D\n";
+    /// let cmudict = CmuDict::from_dict_reader(dict.as_bytes()).unwrap();
+    /// assert!(cmudict.rhyme("far", "tar").unwrap());
+    /// assert_eq!(
+    ///     cmudict.encoding("read"),
+    ///     Ok(Some(vec![
+    ///         vec!["R".to_string(), "EH1".to_string(), "D".to_string()],
+    ///         vec!["R".to_string(), "IY1".to_string(), "D".to_string()]
+    ///     ]))
+    /// );
+    /// ```
+    pub fn from_dict_reader<R: BufRead>(reader: R) -> Result<CmuDict, Error> {
+        let (dict, annotations) = parse_dict(reader)?;
+        Ok(CmuDict::from_map(dict, annotations))
+    }
+
+    pub(crate) fn from_map(
+        dict: HashMap<String, Vec<Vec<String>>>,
+        annotations: Annotations,
+    ) -> CmuDict {
+        let mut cmudict = CmuDict::with_entries(Entries::Map(dict), None);
+        cmudict.annotations = annotations;
+        cmudict
+    }
+
+    /// Initialize the CmuDict from a file in the compact binary format written
+    /// by `write_compact`. The whole file is read into memory; to leave it on
+    /// disk, pass a memory map of it to `from_compact_bytes` instead.
+    pub fn from_compact_file(path: &str) -> Result<CmuDict, Error> {
+        CmuDict::from_compact_bytes(fs::read(path)?)
+    }
+
+    /// Initialize the CmuDict from the compact binary format written by
+    /// `write_compact`. Lookups run directly on `bytes` rather than on a
+    /// deserialized copy, so anything holding the file's bytes works,
+    /// including a memory map of it.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let dict = CmuDict::from_dict_reader("far F AA1 R\ntar T AA1 R\n".as_bytes()).unwrap();
+    /// let compact = CmuDict::from_compact_bytes(dict.to_compact().unwrap()).unwrap();
+    /// assert!(compact.rhyme("far", "tar").unwrap());
+    /// ```
+    pub fn from_compact_bytes<B>(bytes: B) -> Result<CmuDict, Error>
+    where
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        let compact = CompactDict::from_bytes(bytes)?;
+        let annotations = compact.annotations().clone();
+
+        let mut cmudict = CmuDict::with_entries(Entries::Compact(compact), None);
+        cmudict.annotations = annotations;
+        Ok(cmudict)
+    }
+
+    /// The copy of the dictionary baked into the crate with the `embedded`
+    /// feature. No filesystem or network access needed, the dictionary is
+    /// decompressed the first time it's asked for and shared from then on.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::embedded().unwrap();
+    /// assert!(cmudict.rhyme("far", "tar").unwrap());
+    /// ```
+    #[cfg(feature = "embedded")]
+    pub fn embedded() -> Result<CmuDict, Error> {
+        static DECOMPRESSED: OnceLock<Result<Vec<u8>, Error>> = OnceLock::new();
+
+        let bytes = DECOMPRESSED.get_or_init(|| {
+            let mut bytes = vec![];
+            GzDecoder::new(EMBEDDED).read_to_end(&mut bytes)?;
+            Ok(bytes)
+        });
+
+        match bytes {
+            Ok(bytes) => CmuDict::from_compact_bytes(bytes.as_slice()),
+            Err(e) => Err(e.clone()),
         }
     }
 
+    /// The dictionary in the compact binary format, overlays and annotations
+    /// included.
+    pub fn to_compact(&self) -> Result<Vec<u8>, Error> {
+        compact::serialize(self.entries(), &self.annotations)
+    }
+
+    /// Write the dictionary to `path` in the compact binary format, for loading
+    /// with `from_compact_file` or `from_compact_bytes`.
+    pub fn write_compact(&self, path: &str) -> Result<(), Error> {
+        fs::write(path, self.to_compact()?)?;
+        Ok(())
+    }
+
+    /// Initialize the CmuDict from a file in the upstream cmudict.dict format,
+    /// e.g. a vendored copy of the dictionary. Nothing is downloaded.
+    pub fn from_dict_file(path: &str) -> Result<CmuDict, Error> {
+        let file = fs::File::open(path)?;
+        CmuDict::from_dict_reader(io::BufReader::new(file))
+    }
+
+    /// Add a word, or replace all of its pronunciations, on top of the
+    /// dictionary.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let mut cmudict = CmuDict::from_dict_reader("far F AA1 R\n".as_bytes()).unwrap();
+    /// cmudict
+    ///     .insert("Zaphod", vec![vec!["Z".to_string(), "AE1".to_string(), "F".to_string(), "AA0".to_string(), "D".to_string()]])
+    ///     .unwrap();
+    /// assert_eq!(cmudict.syllables("zaphod"), Ok(Some(vec![2])));
+    /// ```
+    pub fn insert(&mut self, w: &str, pronunciations: Vec<Vec<String>>) -> Result<(), Error> {
+        let w = w.to_lowercase();
+        let w = w.trim();
+
+        if pronunciations.is_empty() || pronunciations.iter().any(|p| p.is_empty()) {
+            return Err(Error::InputError(format!("no pronunciation for '{}'", w)));
+        }
+        for phones in &pronunciations {
+            validate_phones(w, phones)?;
+        }
+
+        self.overlay.insert(w.to_string(), Some(pronunciations));
+        self.annotations.remove(w);
+        self.reset_indexes();
+        Ok(())
+    }
+
+    /// Remove a word, returning the pronunciations it had.
+    pub fn remove(&mut self, w: &str) -> Option<Vec<Vec<String>>> {
+        let w = w.to_lowercase();
+        let w = w.trim();
+
+        let removed = self.get(w).map(|p| p.into_owned());
+        self.overlay.insert(w.to_string(), None);
+        self.annotations.remove(w);
+        self.reset_indexes();
+        removed
+    }
+
+    /// Merge an overlay file on top of the dictionary, see
+    /// `load_overlay_reader` for the format. Overlays loaded later win.
+    pub fn load_overlay(&mut self, path: &str) -> Result<(), Error> {
+        let file = fs::File::open(path)?;
+        self.load_overlay_reader(io::BufReader::new(file))
+    }
+
+    /// Merge an overlay on top of the dictionary. Overlays use the
+    /// cmudict.dict line format, where a word
+    ///
+    /// * `word PHONES` replaces the word's pronunciations with those listed in
+    ///   the overlay (variants as `word(2) PHONES` and so on), or adds it,
+    /// * `+word PHONES` adds a pronunciation to the ones the word already has,
+    /// * `-word` removes the word.
+    ///
+    /// Annotations in `# ...` comments are kept as they are for the dictionary.
+    /// The overlay is checked in full before any of it is applied.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let mut cmudict = CmuDict::from_dict_reader(
+    ///     "far F AA1 R\ntar T AA1 R\ncar K AA1 R\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// cmudict
+    ///     .load_overlay_reader("bazaar B AH0 Z AA1 R\n+tar T EH1 R\n-car\n".as_bytes())
+    ///     .unwrap();
+    /// assert_eq!(cmudict.rhymes_for("far"), vec!["bazaar", "tar"]);
+    /// assert_eq!(cmudict.pronunciations("tar").unwrap().unwrap().len(), 2);
+    /// assert_eq!(cmudict.pronunciations("car"), Ok(None));
+    /// ```
+    pub fn load_overlay_reader<R: BufRead>(&mut self, reader: R) -> Result<(), Error> {
+        let mut edits: Vec<(Edit, String, Vec<String>, Option<String>)> = vec![];
+
+        for line in reader.lines() {
+            let line = line?;
+
+            if line.starts_with(";;;") {
+                continue;
+            }
+
+            let (entry, annotation) = split_annotation(&line);
+            let entry = entry
+                .split_whitespace()
+                .map(|s| s.to_string())
+                .collect::<Vec<String>>();
+
+            if let Some((h, t)) = entry.split_first() {
+                let (edit, word) = match h.chars().next() {
+                    Some('+') => (Edit::Add, &h[1..]),
+                    Some('-') => (Edit::Remove, &h[1..]),
+                    _ => (Edit::Replace, h.as_str()),
+                };
+                let word = word.split('(').next().unwrap_or_default().to_lowercase();
+
+                if word.is_empty() {
+                    return Err(Error::InputError(format!(
+                        "no word in overlay line '{}'",
+                        line
+                    )));
+                }
+
+                if edit != Edit::Remove {
+                    if t.is_empty() {
+                        return Err(Error::InputError(format!(
+                            "no pronunciation for '{}' in overlay line '{}'",
+                            word, line
+                        )));
+                    }
+                    validate_phones(&word, t)?;
+                }
+
+                edits.push((edit, word, t.to_vec(), annotation));
+            }
+        }
+
+        // the first plain entry for a word replaces what it had, later ones
+        // are its variants
+        let mut replaced: HashSet<String> = HashSet::new();
+        for (edit, word, phones, annotation) in edits {
+            let (mut pronunciations, mut annotations) = match edit {
+                Edit::Remove => {
+                    replaced.remove(&word);
+                    self.annotations.remove(&word);
+                    self.overlay.insert(word, None);
+                    continue;
+                }
+                Edit::Replace if replaced.insert(word.clone()) => (vec![], vec![]),
+                _ => match self.get(&word) {
+                    Some(p) => (p.into_owned(), self.annotations(&word).unwrap_or_default()),
+                    None => (vec![], vec![]),
+                },
+            };
+
+            if !pronunciations.contains(&phones) {
+                pronunciations.push(phones);
+                annotations.push(annotation);
+            }
+
+            if annotations.iter().any(|a| a.is_some()) {
+                self.annotations.insert(word.clone(), annotations);
+            } else {
+                self.annotations.remove(&word);
+            }
+            self.overlay.insert(word, Some(pronunciations));
+        }
+
+        self.reset_indexes();
+        Ok(())
+    }
+
+    /// The `# ...` comment on each of a word's pronunciations in the
+    /// dictionary text, e.g. `foreign` or `abbrev`, `None` for those without.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "ces S IY1 IY1 EH1 S # abbrev\nces(2) S EH1 Z\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     cmudict.annotations("ces"),
+    ///     Some(vec![Some("abbrev".to_string()), None])
+    /// );
+    /// assert_eq!(cmudict.syllables("ces"), Ok(Some(vec![3, 1])));
+    /// ```
+    pub fn annotations(&self, w: &str) -> Option<Vec<Option<String>>> {
+        let w = w.to_lowercase();
+        let w = w.trim();
+
+        let count = self.get(w)?.len();
+        let mut annotations = self.annotations.get(w).cloned().unwrap_or_default();
+        annotations.resize(count, None);
+        Some(annotations)
+    }
+
+    /// Every word and its pronunciations, overlays included, in no particular
+    /// order.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("far F AA1 R\nread R EH1 D\nread(2) R IY1 D\n".as_bytes()).unwrap();
+    /// let pronunciations: usize = cmudict.iter().map(|(_, p)| p.len()).sum();
+    /// assert_eq!(pronunciations, 3);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Cow<'_, [Vec<String>]>)> + '_ {
+        self.entries()
+    }
+
+    /// Number of words in the dictionary, overlays included.
+    pub fn len(&self) -> usize {
+        let mut len = self.dict.len();
+
+        for (word, pronunciations) in &self.overlay {
+            match (pronunciations.is_some(), self.dict.get(word).is_some()) {
+                (true, false) => len += 1,
+                (false, true) => len -= 1,
+                _ => {}
+            }
+        }
+
+        len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `w` is in the dictionary.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("far F AA1 R\n".as_bytes()).unwrap();
+    /// assert!(cmudict.contains("Far "));
+    /// assert!(!cmudict.contains("tar"));
+    /// ```
+    pub fn contains(&self, w: &str) -> bool {
+        self.get(w.to_lowercase().trim()).is_some()
+    }
+
+    /// Every word in the dictionary, sorted.
+    pub fn words(&self) -> Vec<&str> {
+        let mut words = self.entries().map(|(w, _)| w).collect::<Vec<&str>>();
+        words.sort_unstable();
+        words
+    }
+
+    /// The words passing `filter` serialized to JSON, the format `CmuDict::new`
+    /// loads. Annotations aren't kept, see `write_json`.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("far F AA1 R\ntar T AA1 R\n".as_bytes()).unwrap();
+    /// assert_eq!(
+    ///     cmudict.to_json(|word, _| word == "far").unwrap(),
+    ///     r#"{"far":[["F","AA1","R"]]}"#
+    /// );
+    /// ```
+    pub fn to_json<F>(&self, filter: F) -> Result<String, Error>
+    where
+        F: FnMut(&str, &[Vec<String>]) -> bool,
+    {
+        Ok(serde_json::to_string(&self.subset(filter))?)
+    }
+
+    /// Write the words passing `filter` to `path` as JSON, with the metadata
+    /// sidecar `CmuDict::new` verifies it against. The sidecar carries the
+    /// annotations and this dictionary's upstream provenance, if it has any.
+    pub fn write_json<F>(&self, path: &str, filter: F) -> Result<(), Error>
+    where
+        F: FnMut(&str, &[Vec<String>]) -> bool,
+    {
+        let subset = self.subset(filter);
+        let serialized = serde_json::to_string(&subset)?;
+        let annotations = subset
+            .keys()
+            .filter_map(|w| self.annotations.get(*w).map(|a| (w.to_string(), a.clone())))
+            .collect();
+
+        let metadata = DictMetadata {
+            source: self.metadata.as_ref().and_then(|m| m.source.clone()),
+            upstream_sha256: self
+                .metadata
+                .as_ref()
+                .and_then(|m| m.upstream_sha256.clone()),
+            entries: subset.len(),
+            sha256: sha256_hex(serialized.as_bytes()),
+            annotations,
+        };
+
+        write_serialized(Path::new(path), &serialized, &metadata)
+    }
+
+    /// The words passing `filter` in the upstream cmudict.dict format, sorted,
+    /// with `word(2)` style variants and annotations as `# ...` comments.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "read R EH1 D\nread(2) R IY1 D # past\nfar F AA1 R\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     cmudict.to_dict_text(|_, pronunciations| pronunciations.len() > 1),
+    ///     "read R EH1 D\nread(2) R IY1 D # past\n"
+    /// );
+    /// ```
+    pub fn to_dict_text<F>(&self, filter: F) -> String
+    where
+        F: FnMut(&str, &[Vec<String>]) -> bool,
+    {
+        let mut text = vec![];
+        self.write_dict_text(&mut text, filter)
+            .expect("writing to a Vec can't fail");
+        String::from_utf8(text).expect("dictionary entries are UTF-8")
+    }
+
+    /// Write the words passing `filter` to `path` in the upstream
+    /// cmudict.dict format, for loading with `from_dict_file`.
+    pub fn write_dict<F>(&self, path: &str, filter: F) -> Result<(), Error>
+    where
+        F: FnMut(&str, &[Vec<String>]) -> bool,
+    {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        self.write_dict_text(&mut file, filter)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Provenance of the serialized dictionary this was loaded from. `None` when
+    /// it was parsed from dictionary text directly, or the serialized copy has
+    /// no metadata sidecar and there was no source to regenerate it from.
+    pub fn metadata(&self) -> Option<&DictMetadata> {
+        self.metadata.as_ref()
+    }
+
     /// CMUdict phonetic encoding.
     ///
     /// ```rust
@@ -62,7 +704,129 @@ impl CmuDict {
     /// );
     /// ```
     pub fn encoding(&self, w: &str) -> Result<Option<Vec<Vec<String>>>, Error> {
-        Ok(self.dict.get(w).map(|v| v.to_vec()))
+        Ok(self.get(w).map(|v| v.into_owned()))
+    }
+
+    /// CMUdict phonetic encoding of a word, predicted from its spelling if it
+    /// isn't in the dictionary. `None` only when there's nothing in the word to
+    /// pronounce.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("far F AA1 R\n".as_bytes()).unwrap();
+    ///
+    /// let far = cmudict.encoding_or_predict("far").unwrap().unwrap();
+    /// assert!(!far.predicted);
+    ///
+    /// let yeet = cmudict.encoding_or_predict("yeet").unwrap().unwrap();
+    /// assert!(yeet.predicted);
+    /// assert_eq!(yeet.pronunciations, vec![vec!["Y", "IY1", "T"]]);
+    /// ```
+    pub fn encoding_or_predict(&self, w: &str) -> Result<Option<Encoding>, Error> {
+        let w = w.to_lowercase();
+        let w = w.trim();
+
+        Ok(match self.get(w) {
+            Some(pronunciations) => Some(Encoding {
+                pronunciations: pronunciations.into_owned(),
+                predicted: false,
+            }),
+            None => predict(w).map(|pronunciations| Encoding {
+                pronunciations,
+                predicted: true,
+            }),
+        })
+    }
+
+    /// CMUdict pronunciations of a word as typed phones.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// use ttaw::phoneme::{Phoneme, Stress};
+    /// let cmudict = CmuDict::from_dict_reader("far F AA1 R\n".as_bytes()).unwrap();
+    /// let far = cmudict.pronunciations("Far").unwrap().unwrap();
+    /// assert_eq!(far[0].phones()[1].phoneme, Phoneme::AA);
+    /// assert_eq!(far[0].phones()[1].stress, Some(Stress::Primary));
+    /// ```
+    pub fn pronunciations(&self, w: &str) -> Result<Option<Vec<Pronunciation>>, Error> {
+        match self.get(w.to_lowercase().trim()) {
+            Some(phones) => Ok(Some(
+                phones
+                    .iter()
+                    .map(|p| Pronunciation::from_phones(p))
+                    .collect::<Result<Vec<Pronunciation>, Error>>()?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Number of syllables in each of a word's pronunciations.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("fire F AY1 ER0\nfire(2) F AY1 R\n".as_bytes()).unwrap();
+    /// assert_eq!(cmudict.syllables("fire"), Ok(Some(vec![2, 1])));
+    /// assert_eq!(cmudict.syllables("water"), Ok(None));
+    /// ```
+    pub fn syllables(&self, w: &str) -> Result<Option<Vec<usize>>, Error> {
+        Ok(self
+            .pronunciations(w)?
+            .map(|p| p.iter().map(|p| p.syllable_count()).collect()))
+    }
+
+    /// Each of a word's pronunciations split into syllables, see
+    /// `syllable::syllabify`.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("banana B AH0 N AE1 N AH0\n".as_bytes()).unwrap();
+    /// let banana = &cmudict.syllabify("banana").unwrap().unwrap()[0];
+    /// assert_eq!(banana.len(), 3);
+    /// assert_eq!(banana[1].to_string(), "N AE1");
+    /// ```
+    pub fn syllabify(&self, w: &str) -> Result<Option<Vec<Vec<Syllable>>>, Error> {
+        Ok(self
+            .pronunciations(w)?
+            .map(|p| p.iter().map(syllabify).collect()))
+    }
+
+    /// IPA for each of a word's pronunciations, see `ipa::to_ipa`.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("banana B AH0 N AE1 N AH0\n".as_bytes()).unwrap();
+    /// assert_eq!(cmudict.ipa("banana"), Ok(Some(vec!["bəˈnænə".to_string()])));
+    /// ```
+    pub fn ipa(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        match self.get(w.to_lowercase().trim()) {
+            Some(pronunciations) => Ok(Some(
+                pronunciations
+                    .iter()
+                    .map(|p| ipa::to_ipa(p))
+                    .collect::<Result<Vec<String>, Error>>()?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Stress of each syllable in each of a word's pronunciations, `0` for
+    /// unstressed, `1` for primary and `2` for secondary stress.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("banana B AH0 N AE1 N AH0\n".as_bytes()).unwrap();
+    /// assert_eq!(cmudict.stress_pattern("banana"), Ok(Some(vec![vec![0, 1, 0]])));
+    /// ```
+    pub fn stress_pattern(&self, w: &str) -> Result<Option<Vec<Vec<u8>>>, Error> {
+        Ok(self
+            .pronunciations(w)?
+            .map(|p| p.iter().map(|p| p.stress_pattern()).collect()))
     }
 
     /// Use CMUdict phonetic encoding to determine if two words rhyme.
@@ -80,17 +844,336 @@ impl CmuDict {
     /// assert!(!cmudict.rhyme("comfy", "chair").unwrap());
     /// ```
     pub fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        let options = RhymeOptions {
+            all_variants: true,
+            ..RhymeOptions::default()
+        };
+        self.rhyme_with(a, b, &options)
+    }
+
+    /// Use CMUdict phonetic encoding to determine if two words rhyme, the way
+    /// `rhymes_for_with` would: `b` rhymes with `a` if it'd be in the words
+    /// returned for `a`.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::{CmuDict, RhymeOptions};
+    /// let cmudict = CmuDict::from_dict_reader("far F AA1 R\n".as_bytes()).unwrap();
+    /// assert!(!cmudict.rhyme("far", "blar").unwrap());
+    ///
+    /// let predict = RhymeOptions { predict: true, ..RhymeOptions::default() };
+    /// assert!(cmudict.rhyme_with("far", "blar", &predict).unwrap());
+    /// ```
+    pub fn rhyme_with(&self, a: &str, b: &str, options: &RhymeOptions) -> Result<bool, Error> {
         if let (Some(phones_a), Some(phones_b)) = (
-            self.dict.get(a.to_string().to_lowercase().trim()),
-            self.dict.get(b.to_string().to_lowercase().trim()),
+            self.lookup(a, options.predict),
+            self.lookup(b, options.predict),
         ) {
-            return Ok(eval_rhyme(phones_a, phones_b));
+            let phones_a: Vec<Vec<String>> = self
+                .variants(a, &phones_a, options)
+                .into_iter()
+                .cloned()
+                .collect();
+            let phones_b: Vec<Vec<String>> = self
+                .variants(b, &phones_b, options)
+                .into_iter()
+                .filter(|p| options.syllables.is_none_or(|n| n == syllable_count(p)))
+                .cloned()
+                .collect();
+
+            return Ok(eval_rhyme(&phones_a, &phones_b));
         }
 
         Ok(false)
     }
 
+    /// How two words rhyme, the closest kind of rhyme over all of their
+    /// pronunciations. Words that aren't in the dictionary don't rhyme.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// use ttaw::rhyme::RhymeKind;
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "far F AA1 R\ntar T AA1 R\ncleaver K L IY1 V ER0\nsilver S IH1 L V ER0\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(cmudict.rhyme_kind("far", "tar"), Ok(RhymeKind::Perfect));
+    /// assert_eq!(cmudict.rhyme_kind("cleaver", "silver"), Ok(RhymeKind::Slant));
+    /// assert_eq!(cmudict.rhyme_kind("far", "silver"), Ok(RhymeKind::None));
+    /// ```
+    pub fn rhyme_kind(&self, a: &str, b: &str) -> Result<RhymeKind, Error> {
+        let mut kind = RhymeKind::None;
+
+        if let (Some(pronunciations_a), Some(pronunciations_b)) =
+            (self.pronunciations(a)?, self.pronunciations(b)?)
+        {
+            for a in &pronunciations_a {
+                for b in &pronunciations_b {
+                    kind = kind.closest(rhyme::classify(a, b));
+                }
+            }
+        }
+
+        Ok(kind)
+    }
+
+    /// Weighted phoneme edit distance between two words, the smallest over all
+    /// of their pronunciations, see `distance::phonetic_distance`. `None` if
+    /// either word isn't in the dictionary.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "bat B AE1 T\npat P AE1 T\nbeat B IY1 T\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// let pat = cmudict.phonetic_distance("bat", "pat").unwrap().unwrap();
+    /// let beat = cmudict.phonetic_distance("bat", "beat").unwrap().unwrap();
+    /// assert!(pat < beat);
+    /// assert_eq!(cmudict.phonetic_distance("bat", "zebra"), Ok(None));
+    /// ```
+    pub fn phonetic_distance(&self, a: &str, b: &str) -> Result<Option<f64>, Error> {
+        self.compare(a, b, distance::phonetic_distance, f64::min)
+    }
+
+    /// How alike two words sound from `0.0` to `1.0`, the closest over all of
+    /// their pronunciations, see `distance::similarity`. `None` if either word
+    /// isn't in the dictionary.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader("cat K AE1 T\ncats K AE1 T S\n".as_bytes()).unwrap();
+    /// assert_eq!(cmudict.phonetic_similarity("cat", "cats"), Ok(Some(0.75)));
+    /// ```
+    pub fn phonetic_similarity(&self, a: &str, b: &str) -> Result<Option<f64>, Error> {
+        self.compare(a, b, distance::similarity, f64::max)
+    }
+
+    /// Every word in the dictionary that rhymes with `w`, sorted. Only the first
+    /// pronunciation of each word is considered, see `rhymes_for_with` for
+    /// more.
+    ///
+    /// The first call builds an index of the whole dictionary by rhyming part,
+    /// lookups after that don't scan the dictionary.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "far F AA1 R\ntar T AA1 R\nbazaar B AH0 Z AA1 R\ncart K AA1 R T\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(cmudict.rhymes_for("far"), vec!["bazaar", "tar"]);
+    /// ```
+    pub fn rhymes_for(&self, w: &str) -> Vec<&str> {
+        self.rhymes_for_with(w, &RhymeOptions::default())
+    }
+
+    /// Every word in the dictionary that rhymes with `w`, sorted, narrowed down
+    /// by `options`.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::{CmuDict, RhymeOptions};
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "read R EH1 D\nread(2) R IY1 D\nred R EH1 D\nbead B IY1 D\nstampede S T AE0 M P IY1 D\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(cmudict.rhymes_for("read"), vec!["red"]);
+    ///
+    /// let all = RhymeOptions { all_variants: true, ..RhymeOptions::default() };
+    /// assert_eq!(cmudict.rhymes_for_with("read", &all), vec!["bead", "red", "stampede"]);
+    ///
+    /// let two_syllables = RhymeOptions { syllables: Some(2), all_variants: true, ..RhymeOptions::default() };
+    /// assert_eq!(cmudict.rhymes_for_with("read", &two_syllables), vec!["stampede"]);
+    /// ```
+    pub fn rhymes_for_with(&self, w: &str, options: &RhymeOptions) -> Vec<&str> {
+        let w = w.to_lowercase();
+        let w = w.trim();
+
+        let pronunciations = match self.lookup(w, options.predict) {
+            Some(p) => p,
+            None => return vec![],
+        };
+
+        let index = self
+            .rhyme_index
+            .get_or_init(|| RhymeIndex::build(self.entries()));
+
+        let mut words = vec![];
+        for phones in self.variants(w, &pronunciations, options) {
+            if let Some(part) = rhyming_part(phones) {
+                for entry in index.get(&part) {
+                    if entry.word == w
+                        || (!options.all_variants && entry.variant != 0)
+                        || options.syllables.is_some_and(|n| n != entry.syllables)
+                        || self.excluded(&entry.word, entry.variant, &options.exclude)
+                    {
+                        continue;
+                    }
+
+                    words.push(entry.word.as_str());
+                }
+            }
+        }
+
+        words.sort_unstable();
+        words.dedup();
+        words
+    }
+
+    /// Every other word in the dictionary sharing a pronunciation with `w`,
+    /// sorted.
+    ///
+    /// The first call builds an index of the whole dictionary by pronunciation,
+    /// lookups after that don't scan the dictionary.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::CmuDict;
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "pair P EH1 R\npear P EH1 R\npare P EH1 R\npeer P IH1 R\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(cmudict.homophones("pair"), vec!["pare", "pear"]);
+    /// ```
+    pub fn homophones(&self, w: &str) -> Vec<&str> {
+        self.homophones_with(w, &HomophoneOptions::default())
+    }
+
+    /// Every other word in the dictionary sharing a pronunciation with `w`,
+    /// sorted, widened by `options`.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::{CmuDict, HomophoneOptions};
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "insight IH1 N S AY2 T\nincite IH0 N S AY1 T\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// assert!(cmudict.homophones("insight").is_empty());
+    ///
+    /// let options = HomophoneOptions { ignore_stress: true };
+    /// assert_eq!(cmudict.homophones_with("insight", &options), vec!["incite"]);
+    /// ```
+    pub fn homophones_with(&self, w: &str, options: &HomophoneOptions) -> Vec<&str> {
+        let w = w.to_lowercase();
+        let w = w.trim();
+
+        let pronunciations = match self.get(w) {
+            Some(p) => p,
+            None => return vec![],
+        };
+
+        let index = if options.ignore_stress {
+            &self.unstressed_homophone_index
+        } else {
+            &self.homophone_index
+        }
+        .get_or_init(|| HomophoneIndex::build(self.entries(), options.ignore_stress));
+
+        let mut words = pronunciations
+            .iter()
+            .flat_map(|phones| index.get(phones))
+            .map(|word| word.as_str())
+            .filter(|word| *word != w)
+            .collect::<Vec<&str>>();
+
+        words.sort_unstable();
+        words.dedup();
+        words
+    }
+
+    /// Every word in the dictionary matching `query`, sorted. An `InputError` is
+    /// returned for an invalid stress pattern or phone in the query.
+    ///
+    /// Candidates come from the rhyme index when `rhymes_with` is set and from
+    /// an index of stress patterns when `stress` or `syllables` is, both built
+    /// on first use, so only a query without either scans the dictionary.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::{CmuDict, Query};
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "guitar G IH0 T AA1 R\nbazaar B AH0 Z AA1 R\ntar T AA1 R\ncarpet K AA1 R P AH0 T\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    ///
+    /// let query = Query {
+    ///     stress: Some("01".to_string()),
+    ///     rhymes_with: Some("tar".to_string()),
+    ///     ..Query::default()
+    /// };
+    /// assert_eq!(cmudict.query(&query).unwrap(), vec!["bazaar", "guitar"]);
+    ///
+    /// let query = Query {
+    ///     stress: Some("1*".to_string()),
+    ///     starts_with: vec!["K".to_string(), "AA".to_string()],
+    ///     ..Query::default()
+    /// };
+    /// assert_eq!(cmudict.query(&query).unwrap(), vec!["carpet"]);
+    /// ```
+    pub fn query(&self, query: &Query) -> Result<Vec<&str>, Error> {
+        let filter = QueryFilter::new(query)?;
+
+        let candidates: Vec<(&str, usize)> = match &query.rhymes_with {
+            Some(w) => {
+                let w = w.to_lowercase();
+                let w = w.trim();
+
+                let pronunciations = match self.get(w) {
+                    Some(p) => p,
+                    None => return Ok(vec![]),
+                };
+
+                let index = self
+                    .rhyme_index
+                    .get_or_init(|| RhymeIndex::build(self.entries()));
+
+                pronunciations
+                    .iter()
+                    .filter_map(|phones| rhyming_part(phones))
+                    .flat_map(|part| index.get(&part))
+                    .filter(|entry| entry.word != w)
+                    .map(|entry| (entry.word.as_str(), entry.variant))
+                    .collect()
+            }
+            None if query.stress.is_some() || query.syllables.is_some() => self
+                .stress_index
+                .get_or_init(|| StressIndex::build(self.entries()))
+                .matching(|pattern| filter.matches_stress(pattern)),
+            None => self
+                .entries()
+                .flat_map(|(word, pronunciations)| {
+                    (0..pronunciations.len()).map(move |v| (word, v))
+                })
+                .collect(),
+        };
+
+        let mut words = candidates
+            .into_iter()
+            .filter(|(word, variant)| {
+                self.get(word)
+                    .and_then(|p| {
+                        p.get(*variant)
+                            .and_then(|p| Pronunciation::from_phones(p).ok())
+                    })
+                    .is_some_and(|p| filter.matches(&p))
+            })
+            .map(|(word, _)| word)
+            .collect::<Vec<&str>>();
+
+        words.sort_unstable();
+        words.dedup();
+        Ok(words)
+    }
+
     /// Use CMUdict phonetic encoding to determine if two words alliterate.
+    /// Every pronunciation of both words is considered, and only the first
+    /// consonant sound has to match, see `alliteration_with` for more.
     ///
     /// ```rust
     /// extern crate ttaw;
@@ -105,42 +1188,216 @@ impl CmuDict {
     /// assert!(!cmudict.alliteration("lazy", "dog").unwrap());
     /// ```
     pub fn alliteration(&self, a: &str, b: &str) -> Result<bool, Error> {
-        if Word::parse(Rule::vowel_first, a.get(..1).unwrap_or_default()).is_ok() {
-            return Ok(false);
-        }
-
-        if Word::parse(Rule::vowel_first, b.get(..1).unwrap_or_default()).is_ok() {
-            return Ok(false);
-        }
+        self.alliteration_with(a, b, &AlliterationOptions::default())
+    }
 
+    /// Use CMUdict phonetic encoding to determine if two words alliterate,
+    /// with `options` deciding how much of the words' onsets has to match.
+    ///
+    /// ```rust
+    /// extern crate ttaw;
+    /// use ttaw::cmu::{AlliterationOptions, CmuDict};
+    /// let cmudict = CmuDict::from_dict_reader(
+    ///     "snappy S N AE1 P IY0\nsnails S N EY1 L Z\nsails S EY1 L Z\napple AE1 P AH0 L\nactor AE1 K T ER0\n".as_bytes(),
+    /// )
+    /// .unwrap();
+    /// assert!(cmudict.alliteration("snappy", "sails").unwrap());
+    /// assert!(!cmudict.alliteration("apple", "actor").unwrap());
+    ///
+    /// let clusters = AlliterationOptions { clusters: true, ..AlliterationOptions::default() };
+    /// assert!(cmudict.alliteration_with("snappy", "snails", &clusters).unwrap());
+    /// assert!(!cmudict.alliteration_with("snappy", "sails", &clusters).unwrap());
+    ///
+    /// let vowels = AlliterationOptions { vowels: true, ..AlliterationOptions::default() };
+    /// assert!(cmudict.alliteration_with("apple", "actor", &vowels).unwrap());
+    /// ```
+    pub fn alliteration_with(
+        &self,
+        a: &str,
+        b: &str,
+        options: &AlliterationOptions,
+    ) -> Result<bool, Error> {
         if let (Some(phones_a), Some(phones_b)) = (
-            self.dict.get(a.to_string().to_lowercase().trim()),
-            self.dict.get(b.to_string().to_lowercase().trim()),
+            self.lookup(a, options.predict),
+            self.lookup(b, options.predict),
         ) {
-            return Ok(eval_alliteration(phones_a, phones_b));
+            return Ok(eval_alliteration(&phones_a, &phones_b, options));
         }
 
         Ok(false)
     }
+
+    // The words passing `filter` and their pronunciations, sorted.
+    fn subset<F>(&self, mut filter: F) -> BTreeMap<&str, Cow<'_, [Vec<String>]>>
+    where
+        F: FnMut(&str, &[Vec<String>]) -> bool,
+    {
+        self.entries()
+            .filter(|(word, pronunciations)| filter(word, pronunciations))
+            .collect()
+    }
+
+    fn write_dict_text<W, F>(&self, writer: &mut W, filter: F) -> Result<(), Error>
+    where
+        W: Write,
+        F: FnMut(&str, &[Vec<String>]) -> bool,
+    {
+        for (word, pronunciations) in self.subset(filter) {
+            let annotations = self.annotations.get(word);
+
+            for (i, phones) in pronunciations.iter().enumerate() {
+                if i == 0 {
+                    write!(writer, "{}", word)?;
+                } else {
+                    write!(writer, "{}({})", word, i + 1)?;
+                }
+                write!(writer, " {}", phones.join(" "))?;
+
+                if let Some(Some(annotation)) = annotations.and_then(|a| a.get(i)) {
+                    write!(writer, " # {}", annotation)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Compare every pronunciation of `a` with every one of `b`, keeping the
+    // best score as picked by `best`.
+    fn compare<F, B>(&self, a: &str, b: &str, score: F, best: B) -> Result<Option<f64>, Error>
+    where
+        F: Fn(&Pronunciation, &Pronunciation) -> f64,
+        B: Fn(f64, f64) -> f64,
+    {
+        let (pronunciations_a, pronunciations_b) =
+            match (self.pronunciations(a)?, self.pronunciations(b)?) {
+                (Some(a), Some(b)) => (a, b),
+                _ => return Ok(None),
+            };
+
+        let mut scores = pronunciations_a
+            .iter()
+            .flat_map(|a| pronunciations_b.iter().map(move |b| (a, b)))
+            .map(|(a, b)| score(a, b));
+
+        Ok(scores.next().map(|first| scores.fold(first, &best)))
+    }
+
+    // Drop the lazily built indexes after the entries change.
+    fn reset_indexes(&mut self) {
+        self.rhyme_index = OnceLock::new();
+        self.homophone_index = OnceLock::new();
+        self.unstressed_homophone_index = OnceLock::new();
+        self.stress_index = OnceLock::new();
+    }
+
+    // The pronunciations of `w` to rhyme on: the first or all of them, less
+    // the excluded ones.
+    fn variants<'a>(
+        &self,
+        w: &str,
+        pronunciations: &'a [Vec<String>],
+        options: &RhymeOptions,
+    ) -> Vec<&'a Vec<String>> {
+        let w = w.to_lowercase();
+        let count = if options.all_variants {
+            pronunciations.len()
+        } else {
+            1
+        };
+
+        pronunciations
+            .iter()
+            .take(count)
+            .enumerate()
+            .filter(|(i, _)| !self.excluded(w.trim(), *i, &options.exclude))
+            .map(|(_, p)| p)
+            .collect()
+    }
+
+    // Whether a pronunciation carries one of the `exclude` annotations.
+    fn excluded(&self, w: &str, variant: usize, exclude: &[String]) -> bool {
+        if exclude.is_empty() {
+            return false;
+        }
+
+        match self.annotations.get(w).and_then(|a| a.get(variant)) {
+            Some(Some(annotation)) => annotation
+                .split_whitespace()
+                .any(|a| exclude.iter().any(|e| e == a)),
+            _ => false,
+        }
+    }
+
+    // A word's pronunciations, from the overlay if it's there.
+    fn get(&self, w: &str) -> Option<Cow<'_, [Vec<String>]>> {
+        match self.overlay.get(w) {
+            Some(Some(pronunciations)) => Some(Cow::Borrowed(pronunciations.as_slice())),
+            Some(None) => None,
+            None => self.dict.get(w),
+        }
+    }
+
+    // Every word and its pronunciations, overlay included.
+    fn entries(&self) -> EntryIter<'_> {
+        let overlay = self.overlay.iter().filter_map(|(k, v)| {
+            v.as_ref()
+                .map(|v| (k.as_str(), Cow::Borrowed(v.as_slice())))
+        });
+        let dict = self
+            .dict
+            .iter()
+            .filter(move |(k, _)| !self.overlay.contains_key(*k));
+
+        Box::new(overlay.chain(dict))
+    }
+
+    // A word's pronunciations, predicted if it isn't in the dictionary and
+    // `predict` is set.
+    fn lookup(&self, w: &str, predict: bool) -> Option<Cow<'_, [Vec<String>]>> {
+        let w = w.to_lowercase();
+        let w = w.trim();
+
+        match self.get(w) {
+            Some(pronunciations) => Some(pronunciations),
+            None if predict => self::predict(w).map(Cow::Owned),
+            None => None,
+        }
+    }
+}
+
+fn predict(w: &str) -> Option<Vec<Vec<String>>> {
+    g2p::predict(w).map(|p| vec![p.phones().iter().map(|p| p.to_string()).collect()])
 }
 
-fn rhyming_part(phones: &[String]) -> Option<Vec<String>> {
+pub(crate) fn rhyming_part(phones: &[String]) -> Option<Vec<String>> {
     for (i, s) in phones.iter().rev().enumerate() {
-        if let Some(num) = s.chars().collect::<Vec<char>>().last() {
-            if *num == '1' || *num == '2' {
-                return phones.get(phones.len() - 1 - i..).map(|v| v.to_vec());
-            }
+        if let Ok(Phone {
+            stress: Some(Stress::Primary),
+            ..
+        })
+        | Ok(Phone {
+            stress: Some(Stress::Secondary),
+            ..
+        }) = s.parse::<Phone>()
+        {
+            return phones.get(phones.len() - 1 - i..).map(|v| v.to_vec());
         }
     }
 
     None
 }
 
+// Pronunciations without a stressed vowel have no rhyming part, and like the
+// rhyme index they rhyme with nothing.
 fn eval_rhyme(phones_a: &[Vec<String>], phones_b: &[Vec<String>]) -> bool {
     for a in phones_a {
         for b in phones_b {
-            if rhyming_part(a) == rhyming_part(b) {
-                return true;
+            if let (Some(a), Some(b)) = (rhyming_part(a), rhyming_part(b)) {
+                if a == b {
+                    return true;
+                }
             }
         }
     }
@@ -148,11 +1405,15 @@ fn eval_rhyme(phones_a: &[Vec<String>], phones_b: &[Vec<String>]) -> bool {
     false
 }
 
-fn eval_alliteration(phones_a: &[Vec<String>], phones_b: &[Vec<String>]) -> bool {
+fn eval_alliteration(
+    phones_a: &[Vec<String>],
+    phones_b: &[Vec<String>],
+    options: &AlliterationOptions,
+) -> bool {
     for a in phones_a {
         for b in phones_b {
-            if let (Some(a), Some(b)) = (a.first(), b.first()) {
-                return a == b;
+            if alliterates(&onset(a), &onset(b), options) {
+                return true;
             }
         }
     }
@@ -160,69 +1421,299 @@ fn eval_alliteration(phones_a: &[Vec<String>], phones_b: &[Vec<String>]) -> bool
     false
 }
 
-#[cfg(feature = "reqwest")]
-fn create_if_does_not_exist(path: &Path) -> Result<(), Error> {
-    if !path.exists() {
-        // regenerate if the file isn't there
-        if path.is_dir() {
-            download_and_serialize(&path.join("cmudict.json"))?;
-        } else {
-            download_and_serialize(&path)?;
+fn alliterates(a: &[Phone], b: &[Phone], options: &AlliterationOptions) -> bool {
+    match (a.first(), b.first()) {
+        (Some(first_a), Some(first_b)) if first_a.is_vowel() || first_b.is_vowel() => {
+            options.vowels && first_a.is_vowel() && first_a.phoneme == first_b.phoneme
+        }
+        (Some(first_a), Some(first_b)) => {
+            if options.clusters {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.phoneme == b.phoneme)
+            } else {
+                first_a.phoneme == first_b.phoneme
+            }
         }
+        _ => false,
     }
-    Ok(())
+}
+
+// The consonants a pronunciation starts with, or its first vowel if it starts
+// with one.
+fn onset(phones: &[String]) -> Vec<Phone> {
+    let phones: Vec<Phone> = phones.iter().filter_map(|p| p.parse().ok()).collect();
+
+    match phones.iter().position(|p| p.is_vowel()) {
+        Some(0) => phones[..1].to_vec(),
+        Some(i) => phones[..i].to_vec(),
+        None => phones,
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn default_source() -> Option<Box<dyn DictionarySource>> {
+    Some(Box::new(HttpSource::default()))
 }
 
 #[cfg(not(feature = "reqwest"))]
-fn create_if_does_not_exist(_: &Path) -> Result<(), Error> {
+fn default_source() -> Option<Box<dyn DictionarySource>> {
+    None
+}
+
+fn from_json_file(path: &Path, source: Option<&dyn DictionarySource>) -> Result<CmuDict, Error> {
+    let path = if path.is_dir() {
+        path.join("cmudict.json")
+    } else {
+        path.to_path_buf()
+    };
+
+    // regenerate if the file isn't there, or its metadata isn't, since then
+    // there'd be nothing to verify it against
+    if let Some(source) = source {
+        if !path.exists() || !metadata_path(&path).exists() {
+            fetch_and_serialize(source, &path)?;
+        }
+    }
+
+    let dict_json = fs::read(&path)?;
+    let metadata = read_metadata(&path)?;
+
+    if let Some(ref metadata) = metadata {
+        let sha256 = sha256_hex(&dict_json);
+        if sha256 != metadata.sha256 {
+            return Err(Error::IntegrityError(format!(
+                "{} has SHA-256 {} but {} was recorded when it was serialized",
+                path.display(),
+                sha256,
+                metadata.sha256
+            )));
+        }
+    } else {
+        // only without a source to regenerate it from
+        log::warn!("no metadata for {}, loading it unverified", path.display());
+    }
+
+    let dict: HashMap<String, Vec<Vec<String>>> = serde_json::from_slice(&dict_json)?;
+    for (word, pronunciations) in &dict {
+        for phones in pronunciations {
+            validate_phones(word, phones)?;
+        }
+    }
+
+    if let Some(ref metadata) = metadata {
+        if dict.len() != metadata.entries {
+            return Err(Error::IntegrityError(format!(
+                "{} has {} entries but {} were recorded when it was serialized",
+                path.display(),
+                dict.len(),
+                metadata.entries
+            )));
+        }
+    }
+
+    Ok(CmuDict::with_entries(Entries::Map(dict), metadata))
+}
+
+fn metadata_path(path: &Path) -> PathBuf {
+    let mut meta = path.as_os_str().to_owned();
+    meta.push(".meta");
+    PathBuf::from(meta)
+}
+
+// Write a serialized dictionary and its metadata sidecar, each to a temporary
+// file that's renamed into place so neither is ever left half written. The
+// sidecar goes first: if the dictionary isn't written after it, whatever is
+// at `path` no longer matches and loading it fails the integrity check.
+fn write_serialized(path: &Path, serialized: &str, metadata: &DictMetadata) -> Result<(), Error> {
+    write_atomically(
+        &metadata_path(path),
+        serde_json::to_string(metadata)?.as_bytes(),
+    )?;
+    write_atomically(path, serialized.as_bytes())
+}
+
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
     Ok(())
 }
 
-fn from_json_file(path: &Path) -> Result<HashMap<String, Vec<Vec<String>>>, Error> {
-    let dict_json: String;
+fn read_metadata(path: &Path) -> Result<Option<DictMetadata>, Error> {
+    let meta_path = metadata_path(path);
 
-    create_if_does_not_exist(path)?;
+    if !meta_path.exists() {
+        return Ok(None);
+    }
 
-    dict_json = fs::read_to_string(path)?;
-    let dict: HashMap<String, Vec<Vec<String>>> = serde_json::from_str(&dict_json)?;
-    Ok(dict)
+    match serde_json::from_slice(&fs::read(&meta_path)?) {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(e) => Err(Error::IntegrityError(format!(
+            "unreadable metadata {}: {}",
+            meta_path.display(),
+            e
+        ))),
+    }
 }
 
-#[cfg(feature = "reqwest")]
-pub fn download_and_serialize(path: &Path) -> Result<(), Error> {
-    let dict_string = reqwest::blocking::get(
-        "https://raw.githubusercontent.com/cmusphinx/cmudict/master/cmudict.dict",
-    )?
-    .text()?;
-
-    let cursor = io::Cursor::new(dict_string);
-    let lines = cursor.lines().collect::<Result<Vec<_>, _>>()?;
-
-    let mut dict: HashMap<String, Vec<Vec<String>>> = HashMap::new();
-
-    for line in lines {
-        let entry = line
-            .split_whitespace()
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>();
-
-        if let Some((h, t)) = entry.split_first() {
-            if let Some(key) = h.split('(').collect::<Vec<&str>>().first() {
-                match dict.get_mut(*key) {
-                    Some(v) => {
-                        v.push(t.to_vec());
-                    }
-                    None => {
-                        dict.insert(key.to_string(), vec![t.to_vec()]);
-                    }
-                }
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub(crate) fn validate_phones(word: &str, phones: &[String]) -> Result<(), Error> {
+    for phone in phones {
+        if let Err(e) = phone.parse::<Phone>() {
+            return Err(Error::InputError(format!(
+                "{} in the entry for '{}'",
+                e, word
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// A `Query` with its stress pattern and phones checked and parsed.
+struct QueryFilter {
+    syllables: Option<usize>,
+    stress: Option<Vec<u8>>,
+    starts_with: Vec<Phone>,
+    ends_with: Vec<Phone>,
+}
+
+impl QueryFilter {
+    fn new(query: &Query) -> Result<QueryFilter, Error> {
+        let stress = match &query.stress {
+            Some(pattern) => Some(
+                pattern
+                    .chars()
+                    .map(|c| match c {
+                        '0' | '1' | '2' | '?' | '*' => Ok(c as u8),
+                        _ => Err(Error::InputError(format!(
+                            "invalid stress pattern '{}', expected 0, 1, 2, ? or *",
+                            pattern
+                        ))),
+                    })
+                    .collect::<Result<Vec<u8>, Error>>()?,
+            ),
+            None => None,
+        };
+
+        let phones = |phones: &[String]| {
+            phones
+                .iter()
+                .map(|p| p.to_uppercase().parse())
+                .collect::<Result<Vec<Phone>, Error>>()
+        };
+
+        Ok(QueryFilter {
+            syllables: query.syllables,
+            stress,
+            starts_with: phones(&query.starts_with)?,
+            ends_with: phones(&query.ends_with)?,
+        })
+    }
+
+    // Whether a pronunciation's stress digits, e.g. `[0, 1, 0]`, pass the
+    // syllable count and the stress pattern.
+    fn matches_stress(&self, stress: &[u8]) -> bool {
+        if self.syllables.is_some_and(|n| n != stress.len()) {
+            return false;
+        }
+
+        match &self.stress {
+            Some(pattern) => {
+                let digits = stress.iter().map(|d| b'0' + d).collect::<Vec<u8>>();
+                stress_matches(pattern, &digits)
             }
+            None => true,
+        }
+    }
+
+    fn matches(&self, pronunciation: &Pronunciation) -> bool {
+        let phones = pronunciation.phones();
+
+        self.matches_stress(&pronunciation.stress_pattern())
+            && self.starts_with.len() <= phones.len()
+            && self.ends_with.len() <= phones.len()
+            && phones_match(&self.starts_with, &phones[..self.starts_with.len()])
+            && phones_match(
+                &self.ends_with,
+                &phones[phones.len() - self.ends_with.len()..],
+            )
+    }
+}
+
+// Match stress digits against a pattern with `?` and `*` wildcards.
+fn stress_matches(pattern: &[u8], stress: &[u8]) -> bool {
+    match (pattern.first(), stress.first()) {
+        (Some(b'*'), _) => {
+            stress_matches(&pattern[1..], stress)
+                || (!stress.is_empty() && stress_matches(pattern, &stress[1..]))
+        }
+        (Some(p), Some(s)) if *p == b'?' || p == s => stress_matches(&pattern[1..], &stress[1..]),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+// Phones match when the phonemes do, and the stress too if the pattern has it.
+fn phones_match(pattern: &[Phone], phones: &[Phone]) -> bool {
+    pattern.iter().zip(phones).all(|(p, phone)| {
+        p.phoneme == phone.phoneme && (p.stress.is_none() || p.stress == phone.stress)
+    })
+}
+
+/// Parse the upstream cmudict.dict line format. Variant markers like `word(2)`
+/// are stripped so every pronunciation of a word shares one key.
+fn parse_dict<R: BufRead>(reader: R) -> Result<(Dict, Annotations), Error> {
+    PronouncingDict::default().parse(reader)
+}
+
+/// Split a dictionary line from its trailing `# ...` comment, if it has one.
+pub(crate) fn split_annotation(line: &str) -> (&str, Option<String>) {
+    match line.find('#') {
+        Some(i) => {
+            let annotation = line[i + 1..].trim();
+            (
+                &line[..i],
+                Some(annotation.to_string()).filter(|a| !a.is_empty()),
+            )
         }
+        None => (line, None),
     }
+}
+
+/// Fetch the dictionary from `source` and serialize it at `path`, along with
+/// the `DictMetadata` sidecar used to verify it on load.
+pub fn fetch_and_serialize<S: DictionarySource + ?Sized>(
+    source: &S,
+    path: &Path,
+) -> Result<(), Error> {
+    let dict_text = source.fetch()?;
+    let (dict, annotations) = parse_dict(io::Cursor::new(&dict_text))?;
 
     let serialized = serde_json::to_string(&dict)?;
-    fs::write(path, serialized)?;
-    Ok(())
+    let metadata = DictMetadata {
+        source: source.location(),
+        upstream_sha256: Some(sha256_hex(&dict_text)),
+        entries: dict.len(),
+        sha256: sha256_hex(serialized.as_bytes()),
+        annotations,
+    };
+
+    write_serialized(path, &serialized, &metadata)
+}
+
+/// Download the dictionary from the upstream URL and serialize it at `path`.
+#[cfg(feature = "reqwest")]
+pub fn download_and_serialize(path: &Path) -> Result<(), Error> {
+    fetch_and_serialize(&HttpSource::default(), path)
 }
 
 #[cfg(test)]
@@ -238,11 +1729,87 @@ mod tests {
         assert!(dict.is_ok());
     }
 
+    #[test]
+    fn test_parse_dict() {
+        let (dict, annotations) = parse_dict(
+            ";;; # a license comment\nREAD  R EH1 D\nread(2) R IY1 D\n\nred R EH1 D\n".as_bytes(),
+        )
+        .unwrap();
+
+        assert!(annotations.is_empty());
+        assert_eq!(dict.len(), 2);
+        assert_eq!(
+            dict.get("read"),
+            Some(&vec![
+                vec!["R".to_string(), "EH1".to_string(), "D".to_string()],
+                vec!["R".to_string(), "IY1".to_string(), "D".to_string()]
+            ])
+        );
+        assert_eq!(
+            dict.get("red"),
+            Some(&vec![vec![
+                "R".to_string(),
+                "EH1".to_string(),
+                "D".to_string()
+            ]])
+        );
+    }
+
+    #[test]
+    fn test_parse_dict_missing_phones() {
+        assert!(parse_dict("far F AA1 R\ntar\n".as_bytes()).is_err());
+        assert!(parse_dict("far F AA1 R\ntar # abbrev\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_dict_annotations() {
+        let (dict, annotations) = parse_dict(
+            "d'artagnan D AH0 T AA1 N Y AH0 N # foreign french\nfar F AA1 R\nfar(2) F AA1 R AH0 #\n"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        assert_eq!(dict.get("d'artagnan").unwrap()[0].len(), 8);
+        assert_eq!(
+            annotations.get("d'artagnan"),
+            Some(&vec![Some("foreign french".to_string())])
+        );
+        assert_eq!(dict.get("far").unwrap().len(), 2);
+        assert_eq!(annotations.get("far"), None);
+    }
+
+    #[test]
+    fn test_metadata_path() {
+        assert_eq!(
+            metadata_path(Path::new("dir/cmudict.json")),
+            PathBuf::from("dir/cmudict.json.meta")
+        );
+    }
+
+    #[test]
+    fn test_stress_matches() {
+        assert!(stress_matches(b"010", b"010"));
+        assert!(stress_matches(b"?1?", b"010"));
+        assert!(!stress_matches(b"?1?", b"01"));
+        assert!(stress_matches(b"*1", b"2001"));
+        assert!(stress_matches(b"*", b""));
+        assert!(stress_matches(b"1*0", b"10"));
+        assert!(!stress_matches(b"1*0", b"101"));
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
     #[test]
     fn test_from_json_file() {
         let dir = tempfile::tempdir().unwrap();
         let fpath = dir.path().join("serialized");
-        let dict = from_json_file(&fpath);
+        let dict = from_json_file(&fpath, default_source().as_deref());
         assert!(dict.is_ok());
     }
 }
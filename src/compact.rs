@@ -0,0 +1,418 @@
+//! A compact binary layout for the CMU dictionary that lookups can run on
+//! directly, without deserializing anything up front.
+//!
+//! Everything is little endian:
+//!
+//! ```text
+//! magic            b"TTAWCMU\0"
+//! version          u32
+//! phone count      u32
+//! word count       u32
+//! annotation count u32
+//! phone table      phone count * (u8 length, phone bytes)
+//! word offsets     (word count + 1) * u32, into the word table
+//! entry offsets    (word count + 1) * u32, into the entry table
+//! word table       the words, sorted, back to back
+//! entry table      per word, each pronunciation as (u8 length, phone ids)
+//! annotations      annotation count * (u32 word index, u8 pronunciation
+//!                  index, u8 length, annotation bytes)
+//! ```
+//!
+//! Phones are interned in the phone table and referred to by their index.
+//! Version 1 files have no annotation count or annotations.
+
+use cmu::Annotations;
+use error::Error;
+use phoneme::Phone;
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 8] = b"TTAWCMU\0";
+const VERSION: u32 = 2;
+
+/// A dictionary in the compact layout, backed by anything holding its bytes:
+/// a `Vec<u8>`, a `&'static [u8]` or a memory map of the file.
+pub(crate) struct CompactDict {
+    bytes: Box<dyn AsRef<[u8]> + Send + Sync>,
+    phones: Vec<String>,
+    word_count: usize,
+    word_offsets: usize,
+    entry_offsets: usize,
+    words: usize,
+    entries: usize,
+    annotations: Annotations,
+}
+
+impl CompactDict {
+    pub(crate) fn from_bytes<B>(bytes: B) -> Result<CompactDict, Error>
+    where
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        let data = bytes.as_ref();
+
+        if data.get(..MAGIC.len()) != Some(&MAGIC[..]) {
+            return Err(Error::InputError(
+                "not a compact CMU dictionary".to_string(),
+            ));
+        }
+
+        let version = read_u32(data, 8)?;
+        let (annotation_count, mut pos) = match version {
+            1 => (0, 20),
+            VERSION => (read_u32(data, 20)? as usize, 24),
+            _ => {
+                return Err(Error::InputError(format!(
+                    "unsupported compact CMU dictionary version {}",
+                    version
+                )))
+            }
+        };
+
+        let phone_count = read_u32(data, 12)? as usize;
+        let word_count = read_u32(data, 16)? as usize;
+
+        let mut phones = Vec::with_capacity(phone_count);
+        for _ in 0..phone_count {
+            let len = *data.get(pos).ok_or_else(truncated)? as usize;
+            let phone = data.get(pos + 1..pos + 1 + len).ok_or_else(truncated)?;
+            let phone = String::from_utf8(phone.to_vec()).map_err(|_| truncated())?;
+            phone.parse::<Phone>()?;
+            phones.push(phone);
+            pos += 1 + len;
+        }
+
+        let word_offsets = pos;
+        let entry_offsets = word_offsets + (word_count + 1) * 4;
+        let words = entry_offsets + (word_count + 1) * 4;
+        let entries = words + read_u32(data, entry_offsets - 4)? as usize;
+        let mut pos = entries + read_u32(data, words - 4)? as usize;
+
+        // the few annotated pronunciations are read up front
+        let mut annotated = Vec::with_capacity(annotation_count);
+        for _ in 0..annotation_count {
+            let record = data.get(pos..pos + 6).ok_or_else(truncated)?;
+            let (index, variant) = (read_u32(record, 0)? as usize, record[4] as usize);
+            let len = record[5] as usize;
+            let text = data.get(pos + 6..pos + 6 + len).ok_or_else(truncated)?;
+            let text = String::from_utf8(text.to_vec()).map_err(|_| truncated())?;
+
+            annotated.push((index, variant, text));
+            pos += 6 + len;
+        }
+
+        if pos != data.len() {
+            return Err(truncated());
+        }
+
+        let mut compact = CompactDict {
+            bytes: Box::new(bytes),
+            phones,
+            word_count,
+            word_offsets,
+            entry_offsets,
+            words,
+            entries,
+            annotations: Annotations::new(),
+        };
+
+        for (index, variant, text) in annotated {
+            let word = (index < word_count)
+                .then(|| compact.word_bytes(index))
+                .flatten()
+                .and_then(|w| String::from_utf8(w.to_vec()).ok())
+                .ok_or_else(truncated)?;
+
+            let annotations = compact.annotations.entry(word).or_default();
+            if annotations.len() <= variant {
+                annotations.resize(variant + 1, None);
+            }
+            annotations[variant] = Some(text);
+        }
+
+        Ok(compact)
+    }
+
+    /// The `# ...` comments on pronunciations, see `CmuDict::annotations`.
+    pub(crate) fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    pub(crate) fn get(&self, w: &str) -> Option<Vec<Vec<String>>> {
+        let mut lo = 0;
+        let mut hi = self.word_count;
+
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.word_bytes(mid)?.cmp(w.as_bytes()) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return self.pronunciations(mid),
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.word_count
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, Vec<Vec<String>>)> {
+        (0..self.word_count).filter_map(move |i| {
+            let word = std::str::from_utf8(self.word_bytes(i)?).ok()?;
+            Some((word, self.pronunciations(i)?))
+        })
+    }
+
+    fn word_bytes(&self, i: usize) -> Option<&[u8]> {
+        let data = (*self.bytes).as_ref();
+        let start = read_u32(data, self.word_offsets + i * 4).ok()? as usize;
+        let end = read_u32(data, self.word_offsets + (i + 1) * 4).ok()? as usize;
+        data.get(self.words + start..self.words + end)
+    }
+
+    fn pronunciations(&self, i: usize) -> Option<Vec<Vec<String>>> {
+        let data = (*self.bytes).as_ref();
+        let start = read_u32(data, self.entry_offsets + i * 4).ok()? as usize;
+        let end = read_u32(data, self.entry_offsets + (i + 1) * 4).ok()? as usize;
+        let mut entry = data.get(self.entries + start..self.entries + end)?;
+
+        let mut pronunciations = vec![];
+        while let Some((len, rest)) = entry.split_first() {
+            let ids = rest.get(..*len as usize)?;
+            pronunciations.push(
+                ids.iter()
+                    .map(|id| self.phones.get(*id as usize).cloned())
+                    .collect::<Option<Vec<String>>>()?,
+            );
+            entry = &rest[*len as usize..];
+        }
+
+        Some(pronunciations)
+    }
+}
+
+/// Lay `dict` out in the compact format, with the `annotations` of its words.
+pub(crate) fn serialize<'a, I, P>(dict: I, annotations: &Annotations) -> Result<Vec<u8>, Error>
+where
+    I: Iterator<Item = (&'a str, P)>,
+    P: AsRef<[Vec<String>]>,
+{
+    let mut entries = dict.collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut phones: Vec<&str> = vec![];
+    let mut phone_ids: HashMap<&str, u8> = HashMap::new();
+    let mut word_table = vec![];
+    let mut entry_table = vec![];
+    let mut annotation_table = vec![];
+    let mut annotation_count = 0;
+    let mut word_offsets = vec![0u32];
+    let mut entry_offsets = vec![0u32];
+
+    for (index, (word, pronunciations)) in entries.iter().enumerate() {
+        let count = pronunciations.as_ref().len();
+        let annotated = annotations.get(*word).into_iter().flatten().enumerate();
+        for (variant, annotation) in annotated.take(count) {
+            if let Some(annotation) = annotation {
+                if variant > u8::MAX as usize || annotation.len() > u8::MAX as usize {
+                    return Err(Error::InputError(format!(
+                        "annotation '{}' of '{}' is too long for the compact format",
+                        annotation, word
+                    )));
+                }
+                annotation_table.extend_from_slice(&offset(index)?.to_le_bytes());
+                annotation_table.push(variant as u8);
+                annotation_table.push(annotation.len() as u8);
+                annotation_table.extend_from_slice(annotation.as_bytes());
+                annotation_count += 1;
+            }
+        }
+
+        word_table.extend_from_slice(word.as_bytes());
+        word_offsets.push(offset(word_table.len())?);
+
+        for pronunciation in pronunciations.as_ref() {
+            if pronunciation.len() > u8::MAX as usize {
+                return Err(Error::InputError(format!(
+                    "pronunciation of '{}' is too long for the compact format",
+                    word
+                )));
+            }
+            entry_table.push(pronunciation.len() as u8);
+
+            for phone in pronunciation {
+                let id = match phone_ids.get(phone.as_str()) {
+                    Some(id) => *id,
+                    None => {
+                        if phones.len() > u8::MAX as usize || phone.len() > u8::MAX as usize {
+                            return Err(Error::InputError(format!(
+                                "can't intern phone '{}' in the compact format",
+                                phone
+                            )));
+                        }
+                        phones.push(phone);
+                        phone_ids.insert(phone, (phones.len() - 1) as u8);
+                        (phones.len() - 1) as u8
+                    }
+                };
+                entry_table.push(id);
+            }
+        }
+        entry_offsets.push(offset(entry_table.len())?);
+    }
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&offset(phones.len())?.to_le_bytes());
+    bytes.extend_from_slice(&offset(entries.len())?.to_le_bytes());
+    bytes.extend_from_slice(&offset(annotation_count)?.to_le_bytes());
+
+    for phone in phones {
+        bytes.push(phone.len() as u8);
+        bytes.extend_from_slice(phone.as_bytes());
+    }
+    for o in word_offsets.iter().chain(entry_offsets.iter()) {
+        bytes.extend_from_slice(&o.to_le_bytes());
+    }
+    bytes.extend_from_slice(&word_table);
+    bytes.extend_from_slice(&entry_table);
+    bytes.extend_from_slice(&annotation_table);
+
+    Ok(bytes)
+}
+
+fn offset(len: usize) -> Result<u32, Error> {
+    if len > u32::MAX as usize {
+        return Err(Error::InputError(
+            "dictionary is too large for the compact format".to_string(),
+        ));
+    }
+
+    Ok(len as u32)
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(data.get(pos..pos + 4).ok_or_else(truncated)?);
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn truncated() -> Error {
+    Error::InputError("truncated compact CMU dictionary".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phones(p: &str) -> Vec<String> {
+        p.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut dict: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        dict.insert("tar".to_string(), vec![phones("T AA1 R")]);
+        dict.insert("far".to_string(), vec![phones("F AA1 R")]);
+        dict.insert(
+            "read".to_string(),
+            vec![phones("R EH1 D"), phones("R IY1 D")],
+        );
+
+        let bytes = serialize(
+            dict.iter().map(|(k, v)| (k.as_str(), v.as_slice())),
+            &Annotations::new(),
+        )
+        .unwrap();
+        let compact = CompactDict::from_bytes(bytes).unwrap();
+
+        assert_eq!(compact.word_count, 3);
+        assert_eq!(compact.phones.len(), 7);
+        for (word, pronunciations) in &dict {
+            assert_eq!(compact.get(word).as_ref(), Some(pronunciations));
+        }
+        assert_eq!(compact.get("bar"), None);
+        assert_eq!(compact.get(""), None);
+        assert_eq!(
+            compact.iter().map(|(w, _)| w).collect::<Vec<&str>>(),
+            vec!["far", "read", "tar"]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let bytes = serialize(
+            std::iter::empty::<(&str, &[Vec<String>])>(),
+            &Annotations::new(),
+        )
+        .unwrap();
+        let compact = CompactDict::from_bytes(bytes).unwrap();
+        assert_eq!(compact.word_count, 0);
+        assert_eq!(compact.get("far"), None);
+    }
+
+    #[test]
+    fn truncated_bytes() {
+        let mut dict: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        dict.insert("far".to_string(), vec![phones("F AA1 R")]);
+        let bytes = serialize(
+            dict.iter().map(|(k, v)| (k.as_str(), v.as_slice())),
+            &Annotations::new(),
+        )
+        .unwrap();
+
+        for len in 0..bytes.len() {
+            assert!(CompactDict::from_bytes(bytes[..len].to_vec()).is_err());
+        }
+        assert!(CompactDict::from_bytes(b"not a dictionary".to_vec()).is_err());
+    }
+
+    #[test]
+    fn annotations() {
+        let mut dict: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        dict.insert("far".to_string(), vec![phones("F AA1 R")]);
+        dict.insert(
+            "ces".to_string(),
+            vec![phones("S IY1 Z"), phones("S IY1 IY1 EH1 S")],
+        );
+
+        let mut annotations = Annotations::new();
+        annotations.insert("ces".to_string(), vec![None, Some("abbrev".to_string())]);
+        // dropped, the word isn't in the dictionary
+        annotations.insert("gone".to_string(), vec![Some("foreign".to_string())]);
+
+        let bytes = serialize(
+            dict.iter().map(|(k, v)| (k.as_str(), v.as_slice())),
+            &annotations,
+        )
+        .unwrap();
+        let compact = CompactDict::from_bytes(bytes.clone()).unwrap();
+
+        assert_eq!(compact.annotations().len(), 1);
+        assert_eq!(compact.annotations().get("ces"), annotations.get("ces"));
+
+        for len in 0..bytes.len() {
+            assert!(CompactDict::from_bytes(bytes[..len].to_vec()).is_err());
+        }
+    }
+
+    #[test]
+    fn version_1() {
+        let mut dict: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        dict.insert("far".to_string(), vec![phones("F AA1 R")]);
+        let bytes = serialize(
+            dict.iter().map(|(k, v)| (k.as_str(), v.as_slice())),
+            &Annotations::new(),
+        )
+        .unwrap();
+
+        // no annotation count in the header, nor annotations after the entries
+        let mut v1 = bytes[..20].to_vec();
+        v1[8..12].copy_from_slice(&1u32.to_le_bytes());
+        v1.extend_from_slice(&bytes[24..]);
+
+        let compact = CompactDict::from_bytes(v1).unwrap();
+        assert_eq!(compact.get("far"), Some(vec![phones("F AA1 R")]));
+        assert!(compact.annotations().is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+use phoneme::{Phoneme, Pronunciation};
+
+/// Cost of inserting or deleting a phoneme, and the most a substitution costs.
+const INDEL: f64 = 1.0;
+
+/// How different two phonemes sound, from `0.0` for the same phoneme to `1.0`
+/// for a vowel against a consonant. Distinct consonants cost 0.1, plus 0.4,
+/// 0.3 and 0.2 when they differ in manner, place and voicing. Vowels cost by
+/// how far apart they're articulated. So /P/ → /B/ costs less than /P/ → /T/
+/// and far less than /P/ → /IY/. Stress isn't considered.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::distance::substitution_cost;
+/// use ttaw::phoneme::Phoneme;
+///
+/// assert_eq!(substitution_cost(Phoneme::P, Phoneme::P), 0.0);
+/// assert!(substitution_cost(Phoneme::P, Phoneme::B) < substitution_cost(Phoneme::P, Phoneme::T));
+/// assert_eq!(substitution_cost(Phoneme::P, Phoneme::IY), 1.0);
+/// ```
+pub fn substitution_cost(a: Phoneme, b: Phoneme) -> f64 {
+    if a == b {
+        return 0.0;
+    }
+
+    if a.is_vowel() != b.is_vowel() {
+        return INDEL;
+    }
+
+    if a.is_vowel() {
+        let ((height_a, back_a), (height_b, back_b)) = (vowel_position(a), vowel_position(b));
+        let apart = ((height_a - height_b).powi(2) + (back_a - back_b).powi(2)).sqrt();
+        // the corners of the vowel space are 3.6 apart
+        return 0.1 + 0.5 * apart / 3.6;
+    }
+
+    // consonants that share every feature, like L and R, still differ a
+    // little; counted in tenths so the costs come out exact
+    let mut tenths = 1;
+    if a.manner() != b.manner() {
+        tenths += 4;
+    }
+    if a.place() != b.place() {
+        tenths += 3;
+    }
+    if a.is_voiced() != b.is_voiced() {
+        tenths += 2;
+    }
+
+    f64::from(tenths) / 10.0
+}
+
+/// Weighted edit distance between two pronunciations: insertions and
+/// deletions cost 1.0, substitutions `substitution_cost`.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::distance::phonetic_distance;
+/// use ttaw::phoneme::Pronunciation;
+///
+/// let bat: Pronunciation = "B AE1 T".parse().unwrap();
+/// let pat: Pronunciation = "P AE1 T".parse().unwrap();
+/// let bit: Pronunciation = "B IH1 T".parse().unwrap();
+/// assert!(phonetic_distance(&bat, &pat) < phonetic_distance(&bat, &bit));
+/// ```
+pub fn phonetic_distance(a: &Pronunciation, b: &Pronunciation) -> f64 {
+    let (a, b) = (a.phones(), b.phones());
+
+    let mut previous: Vec<f64> = (0..=b.len()).map(|j| j as f64 * INDEL).collect();
+    let mut current = vec![0.0; b.len() + 1];
+
+    for (i, pa) in a.iter().enumerate() {
+        current[0] = (i + 1) as f64 * INDEL;
+
+        for (j, pb) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + substitution_cost(pa.phoneme, pb.phoneme))
+                .min(previous[j + 1] + INDEL)
+                .min(current[j] + INDEL);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// How alike two pronunciations sound, from `0.0` to `1.0` for the same
+/// phonemes: the phonetic distance relative to the most it could be.
+pub fn similarity(a: &Pronunciation, b: &Pronunciation) -> f64 {
+    let longest = a.phones().len().max(b.phones().len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    1.0 - phonetic_distance(a, b) / (longest as f64 * INDEL)
+}
+
+// Where a vowel is articulated as (height, backness), from low to high and
+// front to back. Diphthongs sit between their start and end.
+fn vowel_position(p: Phoneme) -> (f64, f64) {
+    match p {
+        Phoneme::IY => (3.0, 0.0),
+        Phoneme::IH => (2.5, 0.3),
+        Phoneme::EY => (2.3, 0.2),
+        Phoneme::EH => (1.5, 0.2),
+        Phoneme::AE => (0.5, 0.2),
+        Phoneme::AY => (1.2, 0.6),
+        Phoneme::AH => (1.2, 1.2),
+        Phoneme::ER => (1.5, 1.0),
+        Phoneme::AW => (1.2, 1.4),
+        Phoneme::AA => (0.0, 2.0),
+        Phoneme::AO => (1.0, 2.0),
+        Phoneme::OY => (1.5, 1.6),
+        Phoneme::OW => (1.8, 2.0),
+        Phoneme::UH => (2.5, 1.7),
+        Phoneme::UW => (3.0, 2.0),
+        _ => (0.0, 0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: &str, b: &str) -> f64 {
+        phonetic_distance(&a.parse().unwrap(), &b.parse().unwrap())
+    }
+
+    #[test]
+    fn substitutions() {
+        for a in Phoneme::ALL.iter() {
+            for b in Phoneme::ALL.iter() {
+                let cost = substitution_cost(*a, *b);
+                assert!((0.0..=1.0).contains(&cost));
+                assert_eq!(cost, substitution_cost(*b, *a));
+                assert_eq!(cost == 0.0, a == b);
+            }
+        }
+
+        assert_eq!(substitution_cost(Phoneme::P, Phoneme::B), 0.3);
+        assert_eq!(substitution_cost(Phoneme::L, Phoneme::R), 0.1);
+        assert_eq!(substitution_cost(Phoneme::S, Phoneme::SH), 0.4);
+        assert!(
+            substitution_cost(Phoneme::IY, Phoneme::IH)
+                < substitution_cost(Phoneme::IY, Phoneme::AA)
+        );
+    }
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(distance("K AE1 T", "K AE1 T"), 0.0);
+        // stress doesn't count
+        assert_eq!(distance("K AE1 T", "K AE0 T"), 0.0);
+        assert_eq!(distance("K AE1 T", "K AE1 T S"), 1.0);
+        assert_eq!(distance("", "K AE1 T"), 3.0);
+        assert_eq!(distance("P AE1 T", "B AE1 T"), 0.3);
+        assert!(distance("P AE1 T", "B AE1 T") < distance("P AE1 T", "IY1 AE1 T"));
+    }
+
+    #[test]
+    fn similar() {
+        let cat: Pronunciation = "K AE1 T".parse().unwrap();
+        let cats: Pronunciation = "K AE1 T S".parse().unwrap();
+        assert_eq!(similarity(&cat, &cat), 1.0);
+        assert_eq!(similarity(&cat, &cats), 0.75);
+        assert_eq!(similarity(&cat, &Pronunciation::new(vec![])), 0.0);
+        assert_eq!(
+            similarity(&Pronunciation::new(vec![]), &Pronunciation::new(vec![])),
+            1.0
+        );
+    }
+}
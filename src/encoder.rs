@@ -0,0 +1,249 @@
+use cmu::CmuDict;
+use error::Error;
+use metaphone::{self, Metaphone};
+
+/// The operations every phonetic algorithm in the crate supports, so callers
+/// can pick one by configuration and compare them without separate call
+/// sites. Implemented by `CmuDict`, `metaphone::Metaphone`, `nysiis::Nysiis`,
+/// `mra::MatchRating`, `beider_morse::BeiderMorse` and the encoders in
+/// `soundex`.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::cmu::CmuDict;
+/// use ttaw::encoder::PhoneticEncoder;
+/// use ttaw::metaphone::Metaphone;
+///
+/// let cmudict = CmuDict::from_dict_reader("far F AA1 R\ntar T AA1 R\n".as_bytes()).unwrap();
+/// let encoders: Vec<Box<dyn PhoneticEncoder>> = vec![Box::new(cmudict), Box::new(Metaphone)];
+///
+/// for encoder in &encoders {
+///     assert!(encoder.rhyme("far", "tar").unwrap());
+/// }
+/// ```
+pub trait PhoneticEncoder {
+    /// Every code `w` encodes to, e.g. each CMUdict pronunciation with its
+    /// phones separated by spaces, or the Double Metaphone primary and
+    /// secondary encodings. `None` if the word can't be encoded.
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error>;
+
+    /// Whether two words rhyme.
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error>;
+
+    /// Whether two words alliterate.
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error>;
+
+    /// How alike two words sound from `0.0` to `1.0`, the closest over all of
+    /// their codes. `None` if either word can't be encoded.
+    fn similarity(&self, a: &str, b: &str) -> Result<Option<f64>, Error> {
+        match (self.encode(a)?, self.encode(b)?) {
+            (Some(a), Some(b)) => Ok(a
+                .iter()
+                .flat_map(|a| b.iter().map(move |b| code_similarity(a, b)))
+                .fold(None, |best: Option<f64>, s| {
+                    Some(best.map_or(s, |b| b.max(s)))
+                })),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl PhoneticEncoder for CmuDict {
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        // normalised like every other `CmuDict` lookup, so capitalised words
+        // encode the same as they do with the other encoders
+        Ok(self
+            .encoding(w.to_lowercase().trim())?
+            .map(|pronunciations| pronunciations.iter().map(|p| p.join(" ")).collect()))
+    }
+
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        CmuDict::rhyme(self, a, b)
+    }
+
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error> {
+        self.alliteration(a, b)
+    }
+
+    /// The weighted phoneme similarity, see `CmuDict::phonetic_similarity`.
+    fn similarity(&self, a: &str, b: &str) -> Result<Option<f64>, Error> {
+        self.phonetic_similarity(a, b)
+    }
+}
+
+impl PhoneticEncoder for Metaphone {
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        let encoding = metaphone::encoding(w);
+        if encoding.primary.is_empty() {
+            return Ok(None);
+        }
+
+        let mut codes = vec![encoding.primary];
+        if !codes.contains(&encoding.secondary) {
+            codes.push(encoding.secondary);
+        }
+
+        Ok(Some(codes))
+    }
+
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(metaphone::rhyme(a, b))
+    }
+
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(metaphone::alliteration(a, b))
+    }
+}
+
+// The letters in a word, upper cased and folded to ASCII, which is all the
+// code-based encoders look at. Anything that doesn't fold is dropped.
+pub(crate) fn letters(w: &str) -> Vec<char> {
+    let mut letters = vec![];
+    for c in w.chars().flat_map(char::to_uppercase) {
+        if c.is_ascii_uppercase() {
+            letters.push(c);
+        } else {
+            letters.extend(fold(c).chars());
+        }
+    }
+    letters
+}
+
+// The ASCII spelling of an upper case non-ASCII Latin letter without its
+// diacritics, empty for anything else. 'ß' is already "SS" by the time it gets here.
+fn fold(c: char) -> &'static str {
+    match c {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'Æ' => "AE",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'Ĥ' | 'Ħ' => "H",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'Ĳ' => "IJ",
+        'Ĵ' => "J",
+        'Ķ' => "K",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'Œ' => "OE",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' | 'Ș' => "S",
+        'ẞ' => "SS",
+        'Ţ' | 'Ť' | 'Ŧ' | 'Ț' => "T",
+        'Þ' => "TH",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'Ŵ' => "W",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        _ => "",
+    }
+}
+
+// A code-based encoder's only code for a word, `None` if it's empty.
+pub(crate) fn single_code(code: String) -> Option<Vec<String>> {
+    if code.is_empty() {
+        None
+    } else {
+        Some(vec![code])
+    }
+}
+
+// Codes rhyme when all but their first characters match, the way
+// `metaphone::rhyme` compares encodings. A single-character code has nothing
+// left to compare, so it never rhymes.
+pub(crate) fn codes_rhyme(a: &str, b: &str) -> bool {
+    a.len() > 1 && b.len() > 1 && a.get(1..) == b.get(1..)
+}
+
+// Codes alliterate when they start with the same character.
+pub(crate) fn codes_alliterate(a: &str, b: &str) -> bool {
+    !a.is_empty() && a.get(..1) == b.get(..1)
+}
+
+/// How alike two codes are from `0.0` to `1.0`: one less their Levenshtein
+/// distance over the characters, relative to the longer code.
+pub fn code_similarity(a: &str, b: &str) -> f64 {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + usize::from(ca != cb))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes() {
+        assert_eq!(code_similarity("FR", "FR"), 1.0);
+        assert_eq!(code_similarity("FR", "TR"), 0.5);
+        assert_eq!(code_similarity("KRT", "KR"), 1.0 - 1.0 / 3.0);
+        assert_eq!(code_similarity("", "ABC"), 0.0);
+        assert_eq!(code_similarity("", ""), 1.0);
+        assert!(codes_rhyme("FR", "TR"));
+        assert!(!codes_rhyme("T", "P"));
+        assert!(!codes_rhyme("T", "T"));
+        assert!(!codes_rhyme("", ""));
+    }
+
+    #[test]
+    fn folded_letters() {
+        let folded = |w| letters(w).into_iter().collect::<String>();
+        assert_eq!(folded("Łukasiewicz"), "LUKASIEWICZ");
+        assert_eq!(folded("Żółć-Źdźbło"), "ZOLCZDZBLO");
+        assert_eq!(folded("Straße"), "STRASSE");
+        assert_eq!(folded("Müller"), "MULLER");
+        assert_eq!(folded("O'Brien 2"), "OBRIEN");
+        assert_eq!(folded("Ωмега"), "");
+    }
+
+    #[test]
+    fn metaphone() {
+        assert_eq!(
+            Metaphone.encode("Arnow").unwrap(),
+            Some(vec!["ARN".to_string(), "ARNF".to_string()])
+        );
+        assert_eq!(
+            Metaphone.encode("detestable").unwrap(),
+            Some(vec!["TTSTPL".to_string()])
+        );
+        assert_eq!(Metaphone.encode("").unwrap(), None);
+        assert!(Metaphone.rhyme("far", "tar").unwrap());
+        assert!(Metaphone.alliterate("bouncing", "bears").unwrap());
+        assert_eq!(Metaphone.similarity("far", "tar").unwrap(), Some(0.5));
+        assert_eq!(Metaphone.similarity("far", "").unwrap(), None);
+    }
+
+    #[test]
+    fn case_and_whitespace() {
+        let cmudict = CmuDict::from_dict_reader("far F AA1 R\n".as_bytes()).unwrap();
+        let encoders: Vec<Box<dyn PhoneticEncoder>> = vec![Box::new(cmudict), Box::new(Metaphone)];
+
+        for encoder in &encoders {
+            let far = encoder.encode("far").unwrap();
+            assert!(far.is_some());
+            assert_eq!(encoder.encode("Far").unwrap(), far);
+            assert_eq!(encoder.encode(" FAR ").unwrap(), far);
+        }
+    }
+}
@@ -7,6 +7,7 @@ use std::fmt;
 pub enum Error {
     InputError(String),
     ProgramError(String),
+    IntegrityError(String),
 }
 
 impl fmt::Display for Error {
@@ -15,6 +16,7 @@ impl fmt::Display for Error {
             // Error::ParseError(ref s) => write!(f, "{}", s),
             Error::InputError(ref s) => write!(f, "{}", s),
             Error::ProgramError(ref s) => write!(f, "{}", s),
+            Error::IntegrityError(ref s) => write!(f, "{}", s),
         }
     }
 }
@@ -46,15 +48,20 @@ mod tests {
     fn display() {
         let input = "input error".to_string();
         let progam = "program error".to_string();
+        let integrity = "integrity error".to_string();
 
         assert_eq!(input, format!("{}", Error::InputError(input.clone())));
         assert_eq!(progam, format!("{}", Error::ProgramError(progam.clone())));
+        assert_eq!(
+            integrity,
+            format!("{}", Error::IntegrityError(integrity.clone()))
+        );
     }
 
     #[test]
     fn io_err() {
         let err_str = "IO Errored!";
-        let error = std::io::Error::new(std::io::ErrorKind::Other, err_str);
+        let error = std::io::Error::other(err_str);
 
         assert_eq!(err_str.to_string(), format!("{}", Error::from(error)));
     }
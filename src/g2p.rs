@@ -0,0 +1,356 @@
+//! Letter-to-sound rules for predicting the pronunciation of words that aren't
+//! in the CMU dictionary, e.g. slang, brand names and new coinages.
+//!
+//! The rules are a small hand written set for English spelling, matched left to
+//! right with the longest spelling first. They get common patterns right
+//! (digraphs, silent and "magic" e, soft c and g, r-colored vowels) but they're
+//! a guess: prefer the dictionary whenever the word is in it.
+
+use phoneme::Phoneme::*;
+use phoneme::{Phone, Phoneme, Pronunciation, Stress};
+
+// Where in the word a rule's letters have to be.
+#[derive(Clone, Copy, PartialEq)]
+enum At {
+    Any,
+    Start,
+    End,
+}
+
+struct Rule {
+    letters: &'static str,
+    phonemes: &'static [Phoneme],
+    at: At,
+    /// The rule doesn't apply when followed by one of these letters.
+    not_before: &'static str,
+}
+
+const fn rule(letters: &'static str, phonemes: &'static [Phoneme]) -> Rule {
+    Rule {
+        letters,
+        phonemes,
+        at: At::Any,
+        not_before: "",
+    }
+}
+
+const fn at(letters: &'static str, phonemes: &'static [Phoneme], at: At) -> Rule {
+    Rule {
+        letters,
+        phonemes,
+        at,
+        not_before: "",
+    }
+}
+
+const fn unless(
+    letters: &'static str,
+    phonemes: &'static [Phoneme],
+    not_before: &'static str,
+) -> Rule {
+    Rule {
+        letters,
+        phonemes,
+        at: At::Any,
+        not_before,
+    }
+}
+
+// Tried in order, the first rule that matches wins, so longer spellings and
+// narrower contexts come first.
+const RULES: &[Rule] = &[
+    rule("ough", &[AO]),
+    rule("augh", &[AO]),
+    rule("eigh", &[EY]),
+    rule("tion", &[SH, AH, N]),
+    rule("sion", &[ZH, AH, N]),
+    rule("ture", &[CH, ER]),
+    rule("igh", &[AY]),
+    rule("tch", &[CH]),
+    rule("dge", &[JH]),
+    rule("sch", &[S, K]),
+    rule("air", &[EH, R]),
+    unless("ear", &[IH, R], "aeiouyr"),
+    at("kn", &[N], At::Start),
+    at("wr", &[R], At::Start),
+    at("gn", &[N], At::Start),
+    at("le", &[AH, L], At::End),
+    at("ey", &[IY], At::End),
+    at("ow", &[OW], At::End),
+    rule("ph", &[F]),
+    rule("sh", &[SH]),
+    rule("ch", &[CH]),
+    rule("th", &[TH]),
+    rule("wh", &[W]),
+    rule("zh", &[ZH]),
+    rule("ck", &[K]),
+    rule("gh", &[]),
+    rule("qu", &[K, W]),
+    rule("nk", &[NG, K]),
+    unless("ng", &[NG], "eiy"),
+    unless("ar", &[AA, R], "aeiouyr"),
+    unless("or", &[AO, R], "aeiouyr"),
+    unless("er", &[ER], "aeiouyr"),
+    unless("ir", &[ER], "aeiouyr"),
+    unless("ur", &[ER], "aeiouyr"),
+    rule("ee", &[IY]),
+    rule("ea", &[IY]),
+    rule("ie", &[IY]),
+    rule("ei", &[IY]),
+    rule("oo", &[UW]),
+    rule("ue", &[UW]),
+    rule("ew", &[UW]),
+    rule("ou", &[AW]),
+    rule("ow", &[AW]),
+    rule("oi", &[OY]),
+    rule("oy", &[OY]),
+    rule("ai", &[EY]),
+    rule("ay", &[EY]),
+    rule("ey", &[EY]),
+    rule("au", &[AO]),
+    rule("aw", &[AO]),
+    rule("oa", &[OW]),
+    rule("oe", &[OW]),
+    unless("c", &[K], "eiy"),
+    rule("c", &[S]),
+    unless("g", &[G], "eiy"),
+    rule("g", &[JH]),
+    unless("h", &[], "aeiouy"),
+    rule("a", &[AE]),
+    rule("e", &[EH]),
+    rule("i", &[IH]),
+    rule("o", &[AA]),
+    rule("u", &[AH]),
+    rule("b", &[B]),
+    rule("d", &[D]),
+    rule("f", &[F]),
+    rule("h", &[HH]),
+    rule("j", &[JH]),
+    rule("k", &[K]),
+    rule("l", &[L]),
+    rule("m", &[M]),
+    rule("n", &[N]),
+    rule("p", &[P]),
+    rule("q", &[K]),
+    rule("r", &[R]),
+    rule("s", &[S]),
+    rule("t", &[T]),
+    rule("v", &[V]),
+    rule("w", &[W]),
+    rule("x", &[K, S]),
+    rule("z", &[Z]),
+];
+
+/// Predict the pronunciation of `word` from its spelling, with primary stress
+/// on the first syllable. Anything but ASCII letters is ignored, `None` if
+/// there's nothing left to pronounce.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::g2p::predict;
+///
+/// assert_eq!(predict("yeet").unwrap().to_string(), "Y IY1 T");
+/// assert_eq!(predict("vibe").unwrap().to_string(), "V AY1 B");
+/// assert_eq!(predict("blorf").unwrap().to_string(), "B L AO1 R F");
+/// assert_eq!(predict("1234"), None);
+/// ```
+pub fn predict(word: &str) -> Option<Pronunciation> {
+    let mut letters: Vec<char> = word
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .collect();
+
+    // double consonants sound like one
+    letters.dedup_by(|a, b| a == b && !is_vowel(*a));
+
+    if letters.is_empty() {
+        return None;
+    }
+
+    let vowels = letters.iter().filter(|c| is_vowel(**c)).count();
+    let long = magic_e(&letters);
+    let silent = if long.is_some() || (vowels > 1 && ends_in_silent_e(&letters)) {
+        Some(letters.len() - 1)
+    } else {
+        None
+    };
+    let plural = letters.len() > 1 && letters.ends_with(&['s']);
+
+    let mut phonemes = vec![];
+    let mut i = 0;
+    while i < letters.len() {
+        let c = letters[i];
+        let end = i + 1 == letters.len();
+
+        if Some(i) == silent {
+            i += 1;
+            continue;
+        }
+
+        if Some(i) == long {
+            phonemes.push(long_vowel(c));
+            i += 1;
+            continue;
+        }
+
+        if c == 'y' {
+            phonemes.push(match (i, letters.get(i + 1)) {
+                (0, Some(next)) if is_vowel(*next) => Y,
+                _ if end && vowels > 0 => IY,
+                _ if end => AY,
+                _ => IH,
+            });
+            i += 1;
+            continue;
+        }
+
+        // a vowel ending a word is open, "go", "me", "emu"
+        if end && is_vowel(c) && (vowels == 1 || c == 'o' || c == 'u') {
+            phonemes.push(long_vowel(c));
+            i += 1;
+            continue;
+        }
+
+        match RULES.iter().find(|r| matches(r, &letters, i)) {
+            Some(r) => {
+                phonemes.extend_from_slice(r.phonemes);
+                i += r.letters.len();
+            }
+            None => i += 1,
+        }
+    }
+
+    // plurals and the like are voiced after a voiced sound, "dogs", "bees"
+    let n = phonemes.len();
+    if plural
+        && n > 1
+        && phonemes[n - 1] == S
+        && phonemes[n - 2].is_consonant()
+        && phonemes[n - 2].is_voiced()
+    {
+        phonemes.pop();
+        phonemes.push(Z);
+    }
+
+    let pronunciation = stress(&phonemes);
+    if pronunciation.phones().is_empty() {
+        return None;
+    }
+
+    Some(pronunciation)
+}
+
+fn matches(rule: &Rule, letters: &[char], i: usize) -> bool {
+    let len = rule.letters.len();
+    let end = i + len;
+
+    if end > letters.len() || !rule.letters.chars().eq(letters[i..end].iter().cloned()) {
+        return false;
+    }
+
+    if (rule.at == At::Start && i != 0) || (rule.at == At::End && end != letters.len()) {
+        return false;
+    }
+
+    match letters.get(end) {
+        Some(next) => !rule.not_before.contains(*next),
+        None => true,
+    }
+}
+
+// The vowel lengthened by a silent e, "cake", "vibe", "rose".
+fn magic_e(letters: &[char]) -> Option<usize> {
+    let n = letters.len();
+    if n < 3 || letters[n - 1] != 'e' {
+        return None;
+    }
+
+    let (vowel, consonant) = (letters[n - 3], letters[n - 2]);
+    if !is_vowel(vowel) || is_vowel(consonant) || "wxy".contains(consonant) {
+        return None;
+    }
+
+    // the vowel has to stand alone, "seize" and "house" aren't lengthened
+    if n > 3 && is_vowel(letters[n - 4]) {
+        return None;
+    }
+
+    Some(n - 3)
+}
+
+fn ends_in_silent_e(letters: &[char]) -> bool {
+    let n = letters.len();
+    n > 2 && letters[n - 1] == 'e' && !is_vowel(letters[n - 2]) && letters[n - 2] != 'l'
+}
+
+fn long_vowel(c: char) -> Phoneme {
+    match c {
+        'a' => EY,
+        'e' => IY,
+        'i' => AY,
+        'o' => OW,
+        _ => UW,
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    "aeiou".contains(c)
+}
+
+// Primary stress on the first vowel, the short vowels after it reduce.
+fn stress(phonemes: &[Phoneme]) -> Pronunciation {
+    let mut stressed = false;
+
+    phonemes
+        .iter()
+        .map(|p| {
+            if !p.is_vowel() {
+                return Phone::new(*p, None);
+            }
+
+            if !stressed {
+                stressed = true;
+                return Phone::new(*p, Some(Stress::Primary));
+            }
+
+            let p = match p {
+                AE | EH | AA | AO => AH,
+                p => *p,
+            };
+            Phone::new(p, Some(Stress::Unstressed))
+        })
+        .collect::<Vec<Phone>>()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phones(w: &str) -> String {
+        predict(w).unwrap().to_string()
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(phones("rizz"), "R IH1 Z");
+        assert_eq!(phones("cake"), "K EY1 K");
+        assert_eq!(phones("horse"), "HH AO1 R S");
+        assert_eq!(phones("bottle"), "B AA1 T AH0 L");
+        assert_eq!(phones("knight"), "N AY1 T");
+        assert_eq!(phones("cringe"), "K R IH1 N JH");
+        assert_eq!(phones("happy"), "HH AE1 P IY0");
+        assert_eq!(phones("sky"), "S K AY1");
+        assert_eq!(phones("go"), "G OW1");
+        assert_eq!(phones("dogs"), "D AA1 G Z");
+        assert_eq!(phones("fiction"), "F IH1 K SH AH0 N");
+    }
+
+    #[test]
+    fn ignores_case_and_punctuation() {
+        assert_eq!(predict("Y'all!"), predict("yall"));
+        assert_eq!(predict(""), None);
+        assert_eq!(predict("--"), None);
+    }
+}
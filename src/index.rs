@@ -0,0 +1,158 @@
+use cmu::rhyming_part;
+use phoneme::{Phone, Pronunciation};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// A pronunciation of a word filed under its rhyming part.
+pub(crate) struct RhymeEntry {
+    pub(crate) word: String,
+    /// Which of the word's pronunciations this is, 0 for the first.
+    pub(crate) variant: usize,
+    pub(crate) syllables: usize,
+}
+
+/// Every pronunciation in the dictionary keyed on its rhyming part, so finding
+/// the rhymes for a word is a single lookup rather than a scan.
+pub(crate) struct RhymeIndex {
+    index: HashMap<Vec<String>, Vec<RhymeEntry>>,
+}
+
+impl RhymeIndex {
+    pub(crate) fn build<'a, I>(entries: I) -> RhymeIndex
+    where
+        I: Iterator<Item = (&'a str, Cow<'a, [Vec<String>]>)>,
+    {
+        let mut index: HashMap<Vec<String>, Vec<RhymeEntry>> = HashMap::new();
+
+        for (word, pronunciations) in entries {
+            for (variant, phones) in pronunciations.iter().enumerate() {
+                if let Some(part) = rhyming_part(phones) {
+                    index.entry(part).or_default().push(RhymeEntry {
+                        word: word.to_string(),
+                        variant,
+                        syllables: syllable_count(phones),
+                    });
+                }
+            }
+        }
+
+        for entries in index.values_mut() {
+            entries.sort_by(|a, b| a.word.cmp(&b.word).then(a.variant.cmp(&b.variant)));
+        }
+
+        RhymeIndex { index }
+    }
+
+    pub(crate) fn get(&self, rhyming_part: &[String]) -> &[RhymeEntry] {
+        match self.index.get(rhyming_part) {
+            Some(entries) => entries,
+            None => &[],
+        }
+    }
+}
+
+/// Every word keyed on each of its pronunciations, optionally with the stress
+/// digits dropped, so finding the words that sound alike is a single lookup.
+pub(crate) struct HomophoneIndex {
+    index: HashMap<Vec<String>, Vec<String>>,
+    ignore_stress: bool,
+}
+
+impl HomophoneIndex {
+    pub(crate) fn build<'a, I>(entries: I, ignore_stress: bool) -> HomophoneIndex
+    where
+        I: Iterator<Item = (&'a str, Cow<'a, [Vec<String>]>)>,
+    {
+        let mut index: HashMap<Vec<String>, Vec<String>> = HashMap::new();
+
+        for (word, pronunciations) in entries {
+            for phones in pronunciations.iter() {
+                index
+                    .entry(key(phones, ignore_stress))
+                    .or_default()
+                    .push(word.to_string());
+            }
+        }
+
+        for words in index.values_mut() {
+            words.sort_unstable();
+            words.dedup();
+        }
+
+        HomophoneIndex {
+            index,
+            ignore_stress,
+        }
+    }
+
+    pub(crate) fn get(&self, phones: &[String]) -> &[String] {
+        match self.index.get(&key(phones, self.ignore_stress)) {
+            Some(words) => words,
+            None => &[],
+        }
+    }
+}
+
+/// Every pronunciation in the dictionary keyed on its stress pattern, so a
+/// query by stress or syllable count only looks at the few patterns that
+/// match rather than at every word.
+pub(crate) struct StressIndex {
+    index: HashMap<Vec<u8>, Vec<(String, usize)>>,
+}
+
+impl StressIndex {
+    pub(crate) fn build<'a, I>(entries: I) -> StressIndex
+    where
+        I: Iterator<Item = (&'a str, Cow<'a, [Vec<String>]>)>,
+    {
+        let mut index: HashMap<Vec<u8>, Vec<(String, usize)>> = HashMap::new();
+
+        for (word, pronunciations) in entries {
+            for (variant, phones) in pronunciations.iter().enumerate() {
+                if let Ok(pronunciation) = Pronunciation::from_phones(phones) {
+                    index
+                        .entry(pronunciation.stress_pattern())
+                        .or_default()
+                        .push((word.to_string(), variant));
+                }
+            }
+        }
+
+        StressIndex { index }
+    }
+
+    /// The word and variant of every pronunciation whose stress pattern passes
+    /// `filter`.
+    pub(crate) fn matching<F>(&self, filter: F) -> Vec<(&str, usize)>
+    where
+        F: Fn(&[u8]) -> bool,
+    {
+        self.index
+            .iter()
+            .filter(|(pattern, _)| filter(pattern))
+            .flat_map(|(_, entries)| {
+                entries
+                    .iter()
+                    .map(|(word, variant)| (word.as_str(), *variant))
+            })
+            .collect()
+    }
+}
+
+fn key(phones: &[String], ignore_stress: bool) -> Vec<String> {
+    if !ignore_stress {
+        return phones.to_vec();
+    }
+
+    phones
+        .iter()
+        .map(|p| p.trim_end_matches(|c: char| c.is_ascii_digit()).to_string())
+        .collect()
+}
+
+pub(crate) fn syllable_count(phones: &[String]) -> usize {
+    phones
+        .iter()
+        .filter(|p| p.parse::<Phone>().map(|p| p.is_vowel()).unwrap_or(false))
+        .count()
+}
@@ -0,0 +1,265 @@
+use error::Error;
+use phoneme::{Phone, Phoneme, Pronunciation, Stress};
+use syllable::syllabify;
+
+const PRIMARY: char = 'ˈ';
+const SECONDARY: char = 'ˌ';
+
+/// IPA for CMUdict phones, as returned by `CmuDict::encoding`. Stress marks go
+/// at the start of the stressed syllable, as split by `syllable::syllabify`,
+/// and unstressed AH and ER reduce to ə and ɚ.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::ipa::to_ipa;
+///
+/// assert_eq!(to_ipa(&["P", "ER0", "M", "IY2", "AH0", "B", "IH1", "L", "IH0", "T", "IY0"]).unwrap(), "pɚˌmiəˈbɪlɪti");
+/// assert_eq!(to_ipa(&["AH0", "N", "ER1", "TH", "T"]).unwrap(), "əˈnɝθt");
+/// ```
+pub fn to_ipa<S: AsRef<str>>(phones: &[S]) -> Result<String, Error> {
+    let pronunciation = Pronunciation::from_phones(phones)?;
+    let syllables = syllabify(&pronunciation);
+
+    // without a vowel there's no syllable to stress
+    if syllables.is_empty() {
+        return Ok(pronunciation.phones().iter().map(|p| symbol(*p)).collect());
+    }
+
+    let mut ipa = String::new();
+    for syllable in syllables {
+        match syllable.stress() {
+            Some(Stress::Primary) => ipa.push(PRIMARY),
+            Some(Stress::Secondary) => ipa.push(SECONDARY),
+            _ => {}
+        }
+
+        for phone in syllable.phones() {
+            ipa.push_str(symbol(phone));
+        }
+    }
+
+    Ok(ipa)
+}
+
+/// CMUdict phones for an IPA transcription, the reverse of `to_ipa`. Vowels
+/// following a stress mark get that stress, every other vowel is unstressed.
+/// Slashes, brackets, length marks, tie bars and syllable breaks are ignored.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::ipa::from_ipa;
+///
+/// assert_eq!(from_ipa("/əˈnɝθt/").unwrap(), vec!["AH0", "N", "ER1", "TH", "T"]);
+/// assert_eq!(from_ipa("ˈt͡ʃiːz").unwrap(), vec!["CH", "IY1", "Z"]);
+/// assert!(from_ipa("ʘ").is_err());
+/// ```
+pub fn from_ipa(ipa: &str) -> Result<Vec<String>, Error> {
+    let chars: Vec<char> = ipa
+        .chars()
+        .filter(|c| !"/[]ː.\u{361} ".contains(*c))
+        .collect();
+
+    let mut phones = vec![];
+    let mut stress = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            PRIMARY | '\'' => {
+                stress = Some(Stress::Primary);
+                i += 1;
+                continue;
+            }
+            SECONDARY | ',' => {
+                stress = Some(Stress::Secondary);
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let (phoneme, reduced, len) = match parse_symbol(&chars[i..]) {
+            Some(symbol) => symbol,
+            None => {
+                return Err(Error::InputError(format!(
+                    "unknown IPA symbol '{}' in '{}'",
+                    chars[i], ipa
+                )))
+            }
+        };
+
+        let phone = if !phoneme.is_vowel() {
+            Phone::new(phoneme, None)
+        } else if reduced {
+            Phone::new(phoneme, Some(Stress::Unstressed))
+        } else {
+            Phone::new(phoneme, Some(stress.take().unwrap_or(Stress::Unstressed)))
+        };
+
+        phones.push(phone.to_string());
+        i += len;
+    }
+
+    Ok(phones)
+}
+
+fn symbol(phone: Phone) -> &'static str {
+    use phoneme::Phoneme::*;
+
+    let unstressed = phone.stress == Some(Stress::Unstressed);
+    match phone.phoneme {
+        AA => "ɑ",
+        AE => "æ",
+        AH if unstressed => "ə",
+        AH => "ʌ",
+        AO => "ɔ",
+        AW => "aʊ",
+        AY => "aɪ",
+        B => "b",
+        CH => "tʃ",
+        D => "d",
+        DH => "ð",
+        EH => "ɛ",
+        ER if unstressed => "ɚ",
+        ER => "ɝ",
+        EY => "eɪ",
+        F => "f",
+        G => "ɡ",
+        HH => "h",
+        IH => "ɪ",
+        IY => "i",
+        JH => "dʒ",
+        K => "k",
+        L => "l",
+        M => "m",
+        N => "n",
+        NG => "ŋ",
+        OW => "oʊ",
+        OY => "ɔɪ",
+        P => "p",
+        R => "ɹ",
+        S => "s",
+        SH => "ʃ",
+        T => "t",
+        TH => "θ",
+        UH => "ʊ",
+        UW => "u",
+        V => "v",
+        W => "w",
+        Y => "j",
+        Z => "z",
+        ZH => "ʒ",
+    }
+}
+
+// The phoneme at the start of `chars`, whether it's a reduced vowel, and how
+// many chars it took up. Two letter symbols are tried first.
+fn parse_symbol(chars: &[char]) -> Option<(Phoneme, bool, usize)> {
+    use phoneme::Phoneme::*;
+
+    if chars.len() > 1 {
+        let phoneme = match (chars[0], chars[1]) {
+            ('a', 'ʊ') => Some(AW),
+            ('a', 'ɪ') => Some(AY),
+            ('t', 'ʃ') => Some(CH),
+            ('d', 'ʒ') => Some(JH),
+            ('e', 'ɪ') => Some(EY),
+            ('o', 'ʊ') => Some(OW),
+            ('ɔ', 'ɪ') => Some(OY),
+            ('ɜ', 'ɹ') | ('ɜ', 'r') => Some(ER),
+            _ => None,
+        };
+
+        if let Some(phoneme) = phoneme {
+            return Some((phoneme, false, 2));
+        }
+    }
+
+    let (phoneme, reduced) = match chars.first()? {
+        'ɑ' | 'ɒ' => (AA, false),
+        'æ' => (AE, false),
+        'ə' => (AH, true),
+        'ʌ' => (AH, false),
+        'ɔ' => (AO, false),
+        'b' => (B, false),
+        'd' => (D, false),
+        'ð' => (DH, false),
+        'ɛ' => (EH, false),
+        'ɚ' => (ER, true),
+        'ɝ' | 'ɜ' => (ER, false),
+        'e' => (EY, false),
+        'f' => (F, false),
+        'ɡ' | 'g' => (G, false),
+        'h' => (HH, false),
+        'ɪ' => (IH, false),
+        'i' => (IY, false),
+        'k' => (K, false),
+        'l' => (L, false),
+        'm' => (M, false),
+        'n' => (N, false),
+        'ŋ' => (NG, false),
+        'o' => (OW, false),
+        'p' => (P, false),
+        'ɹ' | 'r' => (R, false),
+        's' => (S, false),
+        'ʃ' => (SH, false),
+        't' => (T, false),
+        'θ' => (TH, false),
+        'ʊ' => (UH, false),
+        'u' => (UW, false),
+        'v' => (V, false),
+        'w' => (W, false),
+        'j' => (Y, false),
+        'z' => (Z, false),
+        'ʒ' => (ZH, false),
+        _ => return None,
+    };
+
+    Some((phoneme, reduced, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for phones in &[
+            vec!["B", "AH0", "N", "AE1", "N", "AH0"],
+            vec!["S", "T", "R", "EH1", "NG", "K", "TH", "S"],
+            vec![
+                "EH2", "K", "S", "P", "L", "AH0", "N", "EY1", "SH", "AH0", "N",
+            ],
+            vec!["B", "AH1", "T", "ER0"],
+            vec!["HH", "AW1", "S"],
+        ] {
+            assert_eq!(&from_ipa(&to_ipa(phones).unwrap()).unwrap(), phones);
+        }
+    }
+
+    #[test]
+    fn stress_at_syllable_start() {
+        assert_eq!(
+            to_ipa(&["B", "AH0", "N", "AE1", "N", "AH0"]).unwrap(),
+            "bəˈnænə"
+        );
+        assert_eq!(
+            to_ipa(&["EH2", "K", "S", "P", "L", "AH0", "N", "EY1", "SH", "AH0", "N"]).unwrap(),
+            "ˌɛkspləˈneɪʃən"
+        );
+        // "nstr" splits as n.str, the longest onset English allows
+        assert_eq!(
+            to_ipa(&["IH0", "N", "S", "T", "R", "AH1", "K", "T"]).unwrap(),
+            "ɪnˈstɹʌkt"
+        );
+        // "ŋ" can't start a syllable
+        assert_eq!(to_ipa(&["S", "IH1", "NG", "ER0"]).unwrap(), "ˈsɪŋɚ");
+        assert_eq!(to_ipa(&["IH0", "N", "S", "AY1", "D"]).unwrap(), "ɪnˈsaɪd");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(to_ipa(&["XX"]).is_err());
+        assert!(from_ipa("bʘt").is_err());
+    }
+}
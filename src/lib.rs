@@ -1,55 +1,75 @@
+//! ## CMU
+//! ```rust
+//! extern crate ttaw;
+//! use ttaw;
+//! let cmudict = ttaw::cmu::CmuDict::new("cmudict.json").unwrap();
+//! assert_eq!(
+//!     cmudict.encoding("permeability"),
+//!     Ok(Some(vec![vec![
+//!         "P".to_string(),
+//!         "ER0".to_string(),
+//!         "M".to_string(),
+//!         "IY2".to_string(),
+//!         "AH0".to_string(),
+//!         "B".to_string(),
+//!         "IH1".to_string(),
+//!         "L".to_string(),
+//!         "IH0".to_string(),
+//!         "T".to_string(),
+//!         "IY0".to_string()
+//!     ]]))
+//! );
+//! assert_eq!(
+//!     cmudict.encoding("unearthed"),
+//!     Ok(Some(vec![vec![
+//!         "AH0".to_string(),
+//!         "N".to_string(),
+//!         "ER1".to_string(),
+//!         "TH".to_string(),
+//!         "T".to_string()
+//!     ]]))
+//! );
+//!
+//! ```
+//!
+//! ## Double Metaphone
+//! ```rust
+//! extern crate ttaw;
+//! use ttaw;
+//!     assert_eq!(ttaw::metaphone::encoding("Arnow").primary, "ARN");
+//!     assert_eq!(ttaw::metaphone::encoding("Arnow").secondary, "ARNF");
+//!
+//!     assert_eq!(ttaw::metaphone::encoding("detestable").primary, "TTSTPL");
+//!     assert_eq!(ttaw::metaphone::encoding("detestable").secondary, "TTSTPL");
+//! ```
+
+#[cfg(feature = "embedded")]
+extern crate flate2;
 extern crate pest;
-///
-/// ## CMU
-/// ```rust
-/// extern crate ttaw;
-/// use ttaw;
-/// let cmudict = ttaw::cmu::CmuDict::new("cmudict.json").unwrap();
-/// assert_eq!(
-///     cmudict.encoding("permeability"),
-///     Ok(Some(vec![vec![
-///         "P".to_string(),
-///         "ER0".to_string(),
-///         "M".to_string(),
-///         "IY2".to_string(),
-///         "AH0".to_string(),
-///         "B".to_string(),
-///         "IH1".to_string(),
-///         "L".to_string(),
-///         "IH0".to_string(),
-///         "T".to_string(),
-///         "IY0".to_string()
-///     ]]))
-/// );
-/// assert_eq!(
-///     cmudict.encoding("unearthed"),
-///     Ok(Some(vec![vec![
-///         "AH0".to_string(),
-///         "N".to_string(),
-///         "ER1".to_string(),
-///         "TH".to_string(),
-///         "T".to_string()
-///     ]]))
-/// );
-///
-/// ```
-///
-/// ## Double Metaphone
-/// ```rust
-/// extern crate ttaw;
-/// use ttaw;
-///     assert_eq!(ttaw::metaphone::encoding("Arnow").primary, "ARN");
-///     assert_eq!(ttaw::metaphone::encoding("Arnow").secondary, "ARNF");
-///
-///     assert_eq!(ttaw::metaphone::encoding("detestable").primary, "TTSTPL");
-///     assert_eq!(ttaw::metaphone::encoding("detestable").secondary, "TTSTPL");
-/// ```
 #[macro_use]
 extern crate pest_derive;
 #[cfg(feature = "reqwest")]
 extern crate reqwest;
+#[macro_use]
+extern crate serde;
 extern crate serde_json;
+extern crate sha2;
+mod compact;
 mod error;
+mod index;
 pub use error::Error;
+pub mod beider_morse;
 pub mod cmu;
+pub mod distance;
+pub mod encoder;
+pub mod g2p;
+pub mod ipa;
 pub mod metaphone;
+pub mod mra;
+pub mod nysiis;
+pub mod phoneme;
+pub mod pronouncing;
+pub mod rhyme;
+pub mod soundex;
+pub mod source;
+pub mod syllable;
@@ -0,0 +1,189 @@
+use encoder::{self, letters, PhoneticEncoder};
+use error::Error;
+
+/// Number of letters in a codex, and the best similarity rating.
+const CODEX_LENGTH: usize = 6;
+
+/// The Match Rating Approach, developed by Western Airlines for matching names
+/// in reservations, as a `PhoneticEncoder`. Words encode to their codex, and
+/// `similarity` is the similarity rating out of 6.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MatchRating;
+
+/// How alike two names are by the Match Rating Approach, see `rating`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rating {
+    /// From 0 to 6, the letters of the longer codex that the other codex
+    /// doesn't match taken from 6.
+    pub similarity: usize,
+    /// The similarity the names need for a match, higher for shorter codexes.
+    pub minimum: usize,
+}
+
+impl Rating {
+    /// Whether the names match.
+    pub fn is_match(&self) -> bool {
+        self.similarity >= self.minimum
+    }
+}
+
+/// The Match Rating Approach codex for `w`: its letters with every vowel but
+/// a leading one dropped and doubled consonants written once, cut down to
+/// the first and last three letters. Empty if `w` has no Latin letters.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::mra::encoding;
+///
+/// assert_eq!(encoding("Byrne"), "BYRN");
+/// assert_eq!(encoding("Catherine"), "CTHRN");
+/// assert_eq!(encoding("Abbotsford"), "ABTFRD");
+/// ```
+pub fn encoding(w: &str) -> String {
+    let mut codex: Vec<char> = vec![];
+
+    for (i, c) in letters(w).into_iter().enumerate() {
+        if i > 0 && "AEIOU".contains(c) {
+            continue;
+        }
+        if codex.last() == Some(&c) {
+            continue;
+        }
+        codex.push(c);
+    }
+
+    if codex.len() > CODEX_LENGTH {
+        codex.drain(3..codex.len() - 3);
+    }
+
+    codex.into_iter().collect()
+}
+
+/// Rate how alike two names are by the Match Rating Approach. Matching
+/// letters are struck from both codexes left to right and then right to
+/// left, and the similarity is 6 less what's left of the longer codex. The
+/// minimum rating for a match depends on the codexes' combined length.
+///
+/// `None` when the names can't be compared: either codex is empty or their
+/// lengths differ by 3 or more.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::mra::{rating, Rating};
+///
+/// assert_eq!(rating("Smith", "Smyth"), Some(Rating { similarity: 5, minimum: 3 }));
+/// assert_eq!(rating("Catherine", "Kathryn"), Some(Rating { similarity: 4, minimum: 3 }));
+/// assert_eq!(rating("Al", "Abbotsford"), None);
+/// ```
+pub fn rating(a: &str, b: &str) -> Option<Rating> {
+    let (a, b): (Vec<char>, Vec<char>) =
+        (encoding(a).chars().collect(), encoding(b).chars().collect());
+
+    if a.is_empty() || b.is_empty() || a.len().abs_diff(b.len()) >= 3 {
+        return None;
+    }
+
+    let minimum = match a.len() + b.len() {
+        0..=4 => 5,
+        5..=7 => 4,
+        8..=11 => 3,
+        _ => 2,
+    };
+
+    let (mut left, mut right) = (vec![true; a.len()], vec![true; b.len()]);
+    for i in 0..a.len().min(b.len()) {
+        if a[i] == b[i] {
+            left[i] = false;
+            right[i] = false;
+        }
+
+        let (end_a, end_b) = (a.len() - 1 - i, b.len() - 1 - i);
+        if a[end_a] == b[end_b] {
+            left[end_a] = false;
+            right[end_b] = false;
+        }
+    }
+
+    let unmatched = |kept: &[bool]| kept.iter().filter(|k| **k).count();
+    let longer = if a.len() > b.len() {
+        unmatched(&left)
+    } else {
+        unmatched(&right)
+    };
+
+    Some(Rating {
+        similarity: CODEX_LENGTH.saturating_sub(longer),
+        minimum,
+    })
+}
+
+/// Use the Match Rating Approach to determine if two names match. Names that
+/// are the same but for case always match.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw;
+/// // Does match
+/// assert!(ttaw::mra::compare("Byrne", "Boern"));
+/// assert!(ttaw::mra::compare("Smith", "Smyth"));
+///
+/// // Does not match
+/// assert!(!ttaw::mra::compare("Smith", "Jones"));
+/// assert!(!ttaw::mra::compare("Al", "Abbotsford"));
+/// ```
+pub fn compare(a: &str, b: &str) -> bool {
+    if !a.trim().is_empty() && a.trim().eq_ignore_ascii_case(b.trim()) {
+        return true;
+    }
+
+    rating(a, b).is_some_and(|r| r.is_match())
+}
+
+impl PhoneticEncoder for MatchRating {
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        Ok(encoder::single_code(encoding(w)))
+    }
+
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(encoder::codes_rhyme(&encoding(a), &encoding(b)))
+    }
+
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(encoder::codes_alliterate(&encoding(a), &encoding(b)))
+    }
+
+    /// The similarity rating out of 6, `0.0` for names too far apart in
+    /// length to be rated.
+    fn similarity(&self, a: &str, b: &str) -> Result<Option<f64>, Error> {
+        if encoding(a).is_empty() || encoding(b).is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(rating(a, b).map_or(0.0, |r| {
+            r.similarity as f64 / CODEX_LENGTH as f64
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codex() {
+        // a leading vowel stays
+        assert_eq!(encoding("Abbe"), "AB");
+        // doubles left behind by dropped vowels go too
+        assert_eq!(encoding("Tata"), "T");
+        assert_eq!(encoding("Schwarzenegger"), "SCHNGR");
+        assert_eq!(encoding(""), "");
+    }
+
+    #[test]
+    fn minimum_ratings() {
+        assert_eq!(rating("Al", "Al").unwrap().minimum, 5);
+        assert_eq!(rating("Byrne", "Boern").unwrap().minimum, 4);
+        assert_eq!(rating("Smith", "Smyth").unwrap().minimum, 3);
+        assert_eq!(rating("Abbotsford", "Abbotsfield").unwrap().minimum, 2);
+    }
+}
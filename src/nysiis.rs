@@ -0,0 +1,177 @@
+use encoder::{self, letters, PhoneticEncoder};
+use error::Error;
+
+/// The New York State Identification and Intelligence System name code, which
+/// maps vowels to A and spellings that sound alike, like PH and FF or MAC and
+/// MC, to the same letters. Keys are six characters long as originally
+/// specified unless `length` says otherwise.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::nysiis::Nysiis;
+///
+/// assert_eq!(Nysiis::default().code("Franklin"), "FRANCL");
+/// assert_eq!(Nysiis { length: None }.code("Franklin"), "FRANCLAN");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nysiis {
+    /// Cut keys to this many characters, `None` to keep them whole.
+    pub length: Option<usize>,
+}
+
+impl Default for Nysiis {
+    fn default() -> Nysiis {
+        Nysiis { length: Some(6) }
+    }
+}
+
+impl Nysiis {
+    /// The NYSIIS key for `w`, empty if it has no Latin letters.
+    pub fn code(&self, w: &str) -> String {
+        let name = letters(w).into_iter().collect::<String>();
+        if name.is_empty() {
+            return String::new();
+        }
+
+        let name = replace_prefix(&name);
+        let name = replace_suffix(&name);
+
+        // translated in place, so each rule sees the letters before it as
+        // already translated
+        let mut chars: Vec<char> = name.chars().collect();
+        let mut key = vec![chars[0]];
+
+        for i in 1..chars.len() {
+            let translated = translate(chars[i - 1], chars[i], &chars[i + 1..]);
+            chars[i..i + translated.len()].copy_from_slice(&translated);
+
+            if chars[i] != chars[i - 1] {
+                key.push(chars[i]);
+            }
+        }
+
+        if key.len() > 1 && key.ends_with(&['S']) {
+            key.pop();
+        }
+        if key.ends_with(&['A', 'Y']) {
+            key.remove(key.len() - 2);
+        }
+        if key.len() > 1 && key.ends_with(&['A']) {
+            key.pop();
+        }
+
+        if let Some(length) = self.length {
+            key.truncate(length);
+        }
+        key.into_iter().collect()
+    }
+}
+
+/// NYSIIS key for `w`, six characters long at most.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::nysiis::encoding;
+///
+/// assert_eq!(encoding("Knight"), "NAGT");
+/// assert_eq!(encoding("Mackenzie"), "MCANSY");
+/// ```
+pub fn encoding(w: &str) -> String {
+    Nysiis::default().code(w)
+}
+
+impl PhoneticEncoder for Nysiis {
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        Ok(encoder::single_code(self.code(w)))
+    }
+
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(encoder::codes_rhyme(&self.code(a), &self.code(b)))
+    }
+
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(encoder::codes_alliterate(&self.code(a), &self.code(b)))
+    }
+}
+
+fn replace_prefix(name: &str) -> String {
+    for (prefix, replacement) in &[
+        ("MAC", "MCC"),
+        ("KN", "NN"),
+        ("K", "C"),
+        ("PH", "FF"),
+        ("PF", "FF"),
+        ("SCH", "SSS"),
+    ] {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            return format!("{}{}", replacement, rest);
+        }
+    }
+
+    name.to_string()
+}
+
+fn replace_suffix(name: &str) -> String {
+    for (suffix, replacement) in &[
+        ("EE", "Y"),
+        ("IE", "Y"),
+        ("DT", "D"),
+        ("RT", "D"),
+        ("RD", "D"),
+        ("NT", "D"),
+        ("ND", "D"),
+    ] {
+        if let Some(rest) = name.strip_suffix(suffix) {
+            // the first letter always stays
+            if !rest.is_empty() {
+                return format!("{}{}", rest, replacement);
+            }
+        }
+    }
+
+    name.to_string()
+}
+
+// The letters `current` and possibly those after it translate to.
+fn translate(previous: char, current: char, next: &[char]) -> Vec<char> {
+    let is_vowel = |c: char| "AEIOU".contains(c);
+
+    match current {
+        'E' if next.first() == Some(&'V') => vec!['A', 'F'],
+        'A' | 'E' | 'I' | 'O' | 'U' => vec!['A'],
+        'Q' => vec!['G'],
+        'Z' => vec!['S'],
+        'M' => vec!['N'],
+        'K' if next.first() == Some(&'N') => vec!['N'],
+        'K' => vec!['C'],
+        'S' if next.starts_with(&['C', 'H']) => vec!['S', 'S', 'S'],
+        'P' if next.first() == Some(&'H') => vec!['F', 'F'],
+        'H' if !is_vowel(previous) || !next.first().is_some_and(|c| is_vowel(*c)) => {
+            vec![previous]
+        }
+        'W' if is_vowel(previous) => vec![previous],
+        c => vec![c],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_and_suffixes() {
+        assert_eq!(replace_prefix("MACKENZIE"), "MCCKENZIE");
+        assert_eq!(replace_prefix("PFISTER"), "FFISTER");
+        assert_eq!(replace_suffix("GRANT"), "GRAD");
+        assert_eq!(replace_suffix("LEE"), "LY");
+        assert_eq!(replace_suffix("EE"), "EE");
+    }
+
+    #[test]
+    fn translations() {
+        assert_eq!(translate('L', 'E', &['V', 'Y']), vec!['A', 'F']);
+        assert_eq!(translate('B', 'H', &['A']), vec!['B']);
+        assert_eq!(translate('A', 'H', &['A']), vec!['H']);
+        assert_eq!(translate('A', 'W', &[]), vec!['A']);
+    }
+}
@@ -0,0 +1,545 @@
+use error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The 39 ARPAbet phonemes used by CMUdict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Phoneme {
+    AA,
+    AE,
+    AH,
+    AO,
+    AW,
+    AY,
+    B,
+    CH,
+    D,
+    DH,
+    EH,
+    ER,
+    EY,
+    F,
+    G,
+    HH,
+    IH,
+    IY,
+    JH,
+    K,
+    L,
+    M,
+    N,
+    NG,
+    OW,
+    OY,
+    P,
+    R,
+    S,
+    SH,
+    T,
+    TH,
+    UH,
+    UW,
+    V,
+    W,
+    Y,
+    Z,
+    ZH,
+}
+
+/// Lexical stress, marked on CMUdict vowels with a trailing 0, 1 or 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stress {
+    Unstressed,
+    Primary,
+    Secondary,
+}
+
+/// How a phoneme is articulated, following the classes in cmudict.phones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Manner {
+    Vowel,
+    Stop,
+    Affricate,
+    Fricative,
+    Aspirate,
+    Liquid,
+    Nasal,
+    Semivowel,
+}
+
+/// Where a consonant is articulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Place {
+    Bilabial,
+    Labiodental,
+    Dental,
+    Alveolar,
+    Postalveolar,
+    Palatal,
+    Velar,
+    LabialVelar,
+    Glottal,
+}
+
+/// A phoneme as it appears in a pronunciation, vowels carry their stress.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::phoneme::{Phone, Phoneme, Stress};
+///
+/// let phone: Phone = "ER1".parse().unwrap();
+/// assert_eq!(phone.phoneme, Phoneme::ER);
+/// assert_eq!(phone.stress, Some(Stress::Primary));
+/// assert!(phone.phoneme.is_vowel());
+/// assert_eq!(phone.to_string(), "ER1");
+///
+/// assert!("TH0".parse::<Phone>().is_err());
+/// assert!("QX".parse::<Phone>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Phone {
+    pub phoneme: Phoneme,
+    pub stress: Option<Stress>,
+}
+
+/// One pronunciation of a word, a sequence of phones.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::phoneme::{Phoneme, Pronunciation};
+///
+/// let pronunciation: Pronunciation = "AH0 N ER1 TH T".parse().unwrap();
+/// assert_eq!(pronunciation.phones().len(), 5);
+/// assert_eq!(pronunciation.phones()[1].phoneme, Phoneme::N);
+/// assert_eq!(pronunciation.to_string(), "AH0 N ER1 TH T");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pronunciation {
+    phones: Vec<Phone>,
+}
+
+impl Phoneme {
+    pub const ALL: [Phoneme; 39] = [
+        Phoneme::AA,
+        Phoneme::AE,
+        Phoneme::AH,
+        Phoneme::AO,
+        Phoneme::AW,
+        Phoneme::AY,
+        Phoneme::B,
+        Phoneme::CH,
+        Phoneme::D,
+        Phoneme::DH,
+        Phoneme::EH,
+        Phoneme::ER,
+        Phoneme::EY,
+        Phoneme::F,
+        Phoneme::G,
+        Phoneme::HH,
+        Phoneme::IH,
+        Phoneme::IY,
+        Phoneme::JH,
+        Phoneme::K,
+        Phoneme::L,
+        Phoneme::M,
+        Phoneme::N,
+        Phoneme::NG,
+        Phoneme::OW,
+        Phoneme::OY,
+        Phoneme::P,
+        Phoneme::R,
+        Phoneme::S,
+        Phoneme::SH,
+        Phoneme::T,
+        Phoneme::TH,
+        Phoneme::UH,
+        Phoneme::UW,
+        Phoneme::V,
+        Phoneme::W,
+        Phoneme::Y,
+        Phoneme::Z,
+        Phoneme::ZH,
+    ];
+
+    /// The ARPAbet symbol.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Phoneme::AA => "AA",
+            Phoneme::AE => "AE",
+            Phoneme::AH => "AH",
+            Phoneme::AO => "AO",
+            Phoneme::AW => "AW",
+            Phoneme::AY => "AY",
+            Phoneme::B => "B",
+            Phoneme::CH => "CH",
+            Phoneme::D => "D",
+            Phoneme::DH => "DH",
+            Phoneme::EH => "EH",
+            Phoneme::ER => "ER",
+            Phoneme::EY => "EY",
+            Phoneme::F => "F",
+            Phoneme::G => "G",
+            Phoneme::HH => "HH",
+            Phoneme::IH => "IH",
+            Phoneme::IY => "IY",
+            Phoneme::JH => "JH",
+            Phoneme::K => "K",
+            Phoneme::L => "L",
+            Phoneme::M => "M",
+            Phoneme::N => "N",
+            Phoneme::NG => "NG",
+            Phoneme::OW => "OW",
+            Phoneme::OY => "OY",
+            Phoneme::P => "P",
+            Phoneme::R => "R",
+            Phoneme::S => "S",
+            Phoneme::SH => "SH",
+            Phoneme::T => "T",
+            Phoneme::TH => "TH",
+            Phoneme::UH => "UH",
+            Phoneme::UW => "UW",
+            Phoneme::V => "V",
+            Phoneme::W => "W",
+            Phoneme::Y => "Y",
+            Phoneme::Z => "Z",
+            Phoneme::ZH => "ZH",
+        }
+    }
+
+    pub fn is_vowel(self) -> bool {
+        self.manner() == Manner::Vowel
+    }
+
+    pub fn is_consonant(self) -> bool {
+        !self.is_vowel()
+    }
+
+    pub fn manner(self) -> Manner {
+        match self {
+            Phoneme::AA
+            | Phoneme::AE
+            | Phoneme::AH
+            | Phoneme::AO
+            | Phoneme::AW
+            | Phoneme::AY
+            | Phoneme::EH
+            | Phoneme::ER
+            | Phoneme::EY
+            | Phoneme::IH
+            | Phoneme::IY
+            | Phoneme::OW
+            | Phoneme::OY
+            | Phoneme::UH
+            | Phoneme::UW => Manner::Vowel,
+            Phoneme::B | Phoneme::D | Phoneme::G | Phoneme::K | Phoneme::P | Phoneme::T => {
+                Manner::Stop
+            }
+            Phoneme::CH | Phoneme::JH => Manner::Affricate,
+            Phoneme::DH
+            | Phoneme::F
+            | Phoneme::S
+            | Phoneme::SH
+            | Phoneme::TH
+            | Phoneme::V
+            | Phoneme::Z
+            | Phoneme::ZH => Manner::Fricative,
+            Phoneme::HH => Manner::Aspirate,
+            Phoneme::L | Phoneme::R => Manner::Liquid,
+            Phoneme::M | Phoneme::N | Phoneme::NG => Manner::Nasal,
+            Phoneme::W | Phoneme::Y => Manner::Semivowel,
+        }
+    }
+
+    /// Place of articulation, `None` for vowels.
+    pub fn place(self) -> Option<Place> {
+        match self {
+            Phoneme::B | Phoneme::M | Phoneme::P => Some(Place::Bilabial),
+            Phoneme::F | Phoneme::V => Some(Place::Labiodental),
+            Phoneme::DH | Phoneme::TH => Some(Place::Dental),
+            Phoneme::D
+            | Phoneme::L
+            | Phoneme::N
+            | Phoneme::R
+            | Phoneme::S
+            | Phoneme::T
+            | Phoneme::Z => Some(Place::Alveolar),
+            Phoneme::CH | Phoneme::JH | Phoneme::SH | Phoneme::ZH => Some(Place::Postalveolar),
+            Phoneme::Y => Some(Place::Palatal),
+            Phoneme::G | Phoneme::K | Phoneme::NG => Some(Place::Velar),
+            Phoneme::W => Some(Place::LabialVelar),
+            Phoneme::HH => Some(Place::Glottal),
+            _ => None,
+        }
+    }
+
+    /// Whether the vocal folds vibrate, true for all vowels.
+    pub fn is_voiced(self) -> bool {
+        !matches!(
+            self,
+            Phoneme::CH
+                | Phoneme::F
+                | Phoneme::HH
+                | Phoneme::K
+                | Phoneme::P
+                | Phoneme::S
+                | Phoneme::SH
+                | Phoneme::T
+                | Phoneme::TH
+        )
+    }
+}
+
+impl FromStr for Phoneme {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Phoneme, Error> {
+        match Phoneme::ALL.iter().find(|p| p.symbol() == s) {
+            Some(p) => Ok(*p),
+            None => Err(Error::InputError(format!("unknown phoneme '{}'", s))),
+        }
+    }
+}
+
+impl fmt::Display for Phoneme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+impl Stress {
+    /// The CMUdict stress digit.
+    pub fn digit(self) -> char {
+        match self {
+            Stress::Unstressed => '0',
+            Stress::Primary => '1',
+            Stress::Secondary => '2',
+        }
+    }
+
+    pub fn from_digit(c: char) -> Option<Stress> {
+        match c {
+            '0' => Some(Stress::Unstressed),
+            '1' => Some(Stress::Primary),
+            '2' => Some(Stress::Secondary),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Stress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Stress, Error> {
+        let mut chars = s.chars();
+        match (chars.next().and_then(Stress::from_digit), chars.next()) {
+            (Some(stress), None) => Ok(stress),
+            _ => Err(Error::InputError(format!("unknown stress '{}'", s))),
+        }
+    }
+}
+
+impl fmt::Display for Stress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.digit())
+    }
+}
+
+impl Phone {
+    pub fn new(phoneme: Phoneme, stress: Option<Stress>) -> Phone {
+        Phone { phoneme, stress }
+    }
+
+    pub fn is_vowel(self) -> bool {
+        self.phoneme.is_vowel()
+    }
+
+    pub fn is_consonant(self) -> bool {
+        self.phoneme.is_consonant()
+    }
+}
+
+impl FromStr for Phone {
+    type Err = Error;
+
+    /// Vowels may leave out their stress digit, consonants can't have one.
+    fn from_str(s: &str) -> Result<Phone, Error> {
+        let (symbol, stress) = match s.chars().last().and_then(Stress::from_digit) {
+            Some(stress) => (&s[..s.len() - 1], Some(stress)),
+            None => (s, None),
+        };
+
+        let phoneme = match symbol.parse::<Phoneme>() {
+            Ok(phoneme) => phoneme,
+            Err(_) => return Err(Error::InputError(format!("unknown phone '{}'", s))),
+        };
+
+        if stress.is_some() && phoneme.is_consonant() {
+            return Err(Error::InputError(format!(
+                "consonant '{}' can't carry stress",
+                s
+            )));
+        }
+
+        Ok(Phone { phoneme, stress })
+    }
+}
+
+impl fmt::Display for Phone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.stress {
+            Some(stress) => write!(f, "{}{}", self.phoneme, stress),
+            None => write!(f, "{}", self.phoneme),
+        }
+    }
+}
+
+impl Pronunciation {
+    pub fn new(phones: Vec<Phone>) -> Pronunciation {
+        Pronunciation { phones }
+    }
+
+    pub fn phones(&self) -> &[Phone] {
+        &self.phones
+    }
+
+    /// Number of syllables, one per vowel.
+    pub fn syllable_count(&self) -> usize {
+        self.phones.iter().filter(|p| p.is_vowel()).count()
+    }
+
+    /// The stress digit of each syllable in order, e.g. `[0, 1, 0]` for
+    /// "banana". Vowels without a stress marker count as unstressed.
+    pub fn stress_pattern(&self) -> Vec<u8> {
+        self.phones
+            .iter()
+            .filter(|p| p.is_vowel())
+            .map(|p| match p.stress {
+                Some(Stress::Primary) => 1,
+                Some(Stress::Secondary) => 2,
+                Some(Stress::Unstressed) | None => 0,
+            })
+            .collect()
+    }
+
+    /// Parse phones as they're stored in CMUdict, e.g. `["AH0", "N", "ER1"]`.
+    pub fn from_phones<S: AsRef<str>>(phones: &[S]) -> Result<Pronunciation, Error> {
+        Ok(Pronunciation {
+            phones: phones
+                .iter()
+                .map(|p| p.as_ref().parse())
+                .collect::<Result<Vec<Phone>, Error>>()?,
+        })
+    }
+}
+
+impl From<Vec<Phone>> for Pronunciation {
+    fn from(phones: Vec<Phone>) -> Pronunciation {
+        Pronunciation::new(phones)
+    }
+}
+
+impl AsRef<[Phone]> for Pronunciation {
+    fn as_ref(&self) -> &[Phone] {
+        &self.phones
+    }
+}
+
+impl FromStr for Pronunciation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Pronunciation, Error> {
+        Pronunciation::from_phones(&s.split_whitespace().collect::<Vec<&str>>())
+    }
+}
+
+impl fmt::Display for Pronunciation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let phones = self
+            .phones
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}", phones.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols_round_trip() {
+        for phoneme in Phoneme::ALL.iter() {
+            assert_eq!(phoneme.symbol().parse::<Phoneme>(), Ok(*phoneme));
+        }
+    }
+
+    #[test]
+    fn vowels_and_consonants() {
+        let vowels = Phoneme::ALL.iter().filter(|p| p.is_vowel()).count();
+        let consonants = Phoneme::ALL.iter().filter(|p| p.is_consonant()).count();
+        assert_eq!(vowels, 15);
+        assert_eq!(consonants, 24);
+
+        for phoneme in Phoneme::ALL.iter() {
+            assert_eq!(phoneme.place().is_none(), phoneme.is_vowel());
+            if phoneme.is_vowel() {
+                assert!(phoneme.is_voiced());
+            }
+        }
+    }
+
+    #[test]
+    fn phones() {
+        assert_eq!(
+            "AH0".parse::<Phone>(),
+            Ok(Phone::new(Phoneme::AH, Some(Stress::Unstressed)))
+        );
+        assert_eq!(
+            "IY2".parse::<Phone>(),
+            Ok(Phone::new(Phoneme::IY, Some(Stress::Secondary)))
+        );
+        assert_eq!("AH".parse::<Phone>(), Ok(Phone::new(Phoneme::AH, None)));
+        assert_eq!("ZH".parse::<Phone>(), Ok(Phone::new(Phoneme::ZH, None)));
+
+        assert!("".parse::<Phone>().is_err());
+        assert!("1".parse::<Phone>().is_err());
+        assert!("AH3".parse::<Phone>().is_err());
+        assert!("ah0".parse::<Phone>().is_err());
+        assert!("K1".parse::<Phone>().is_err());
+        assert!("#".parse::<Phone>().is_err());
+    }
+
+    #[test]
+    fn stress() {
+        assert_eq!("1".parse::<Stress>(), Ok(Stress::Primary));
+        assert!("12".parse::<Stress>().is_err());
+        assert!("".parse::<Stress>().is_err());
+        assert_eq!(Stress::Secondary.to_string(), "2");
+    }
+
+    #[test]
+    fn syllables() {
+        let banana: Pronunciation = "B AH0 N AE1 N AH0".parse().unwrap();
+        assert_eq!(banana.syllable_count(), 3);
+        assert_eq!(banana.stress_pattern(), vec![0, 1, 0]);
+
+        let unstressed: Pronunciation = "B AH N AE N AH".parse().unwrap();
+        assert_eq!(unstressed.stress_pattern(), vec![0, 0, 0]);
+
+        let hmm: Pronunciation = "HH M".parse().unwrap();
+        assert_eq!(hmm.syllable_count(), 0);
+        assert!(hmm.stress_pattern().is_empty());
+    }
+
+    #[test]
+    fn features() {
+        assert_eq!(Phoneme::P.manner(), Manner::Stop);
+        assert_eq!(Phoneme::P.place(), Some(Place::Bilabial));
+        assert!(!Phoneme::P.is_voiced());
+        assert!(Phoneme::B.is_voiced());
+        assert_eq!(Phoneme::SH.place(), Some(Place::Postalveolar));
+        assert_eq!(Phoneme::W.manner(), Manner::Semivowel);
+        assert_eq!(Phoneme::HH.manner(), Manner::Aspirate);
+    }
+}
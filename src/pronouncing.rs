@@ -0,0 +1,230 @@
+use cmu::{split_annotation, validate_phones, Annotations, CmuDict, Dict};
+use error::Error;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead};
+
+/// The layout of an ARPAbet pronouncing dictionary, for loading lexicons other
+/// than upstream CMUdict into a `CmuDict`: the LibriSpeech lexicon, Montreal
+/// Forced Aligner dictionaries with pronunciation probabilities, or lexicons
+/// with syllable boundaries marked. The default is the cmudict.dict layout.
+///
+/// Every layout has one pronunciation per line, the word first. Lines starting
+/// with `;;;` are skipped, `word(2)` style variant markers are stripped, phones
+/// are upper cased and a trailing `# ...` comment is kept as the
+/// pronunciation's annotation.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::pronouncing::PronouncingDict;
+///
+/// let mfa = PronouncingDict {
+///     tab_separated: true,
+///     probabilities: true,
+///     ..PronouncingDict::default()
+/// };
+/// let dict = mfa
+///     .load_reader("read\t0.4\tR EH1 D\nread\t0.99\tR IY1 D\nbead\t1.0\tB IY1 D\n".as_bytes())
+///     .unwrap();
+///
+/// // the likeliest pronunciation comes first
+/// assert_eq!(dict.rhymes_for("read"), vec!["bead"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PronouncingDict {
+    /// Columns are separated by tabs rather than any whitespace, so words can
+    /// contain spaces. The phones may still be separated by spaces.
+    pub tab_separated: bool,
+    /// A probability follows each word, and possibly more numbers after it
+    /// (MFA's silence probabilities) which are ignored. A word's
+    /// pronunciations are ordered likeliest first, entries without one count
+    /// as certain.
+    pub probabilities: bool,
+    /// Characters marking syllable boundaries between the phones, e.g. `-` or
+    /// `.`, which are dropped.
+    pub syllable_markers: Vec<char>,
+}
+
+impl PronouncingDict {
+    /// Load the dictionary at `path` in this layout.
+    pub fn load(&self, path: &str) -> Result<CmuDict, Error> {
+        let file = fs::File::open(path)?;
+        self.load_reader(io::BufReader::new(file))
+    }
+
+    /// Load dictionary text in this layout.
+    pub fn load_reader<R: BufRead>(&self, reader: R) -> Result<CmuDict, Error> {
+        let (dict, annotations) = self.parse(reader)?;
+        Ok(CmuDict::from_map(dict, annotations))
+    }
+
+    pub(crate) fn parse<R: BufRead>(&self, reader: R) -> Result<(Dict, Annotations), Error> {
+        let mut dict: HashMap<String, Vec<Entry>> = HashMap::new();
+
+        for line in reader.lines() {
+            let line = line?;
+
+            // the older cmudict-0.7b releases carry a license header in ';;;' comments
+            if line.starts_with(";;;") {
+                continue;
+            }
+
+            let (entry, annotation) = split_annotation(&line);
+            let (word, mut fields) = if self.tab_separated {
+                let mut columns = entry.split('\t');
+                let word = columns.next().unwrap_or_default().trim();
+                (word, columns.flat_map(|c| c.split_whitespace()).collect())
+            } else {
+                let mut columns = entry.split_whitespace();
+                let word = columns.next().unwrap_or_default();
+                (word, columns.collect::<Vec<&str>>())
+            };
+
+            if word.is_empty() {
+                continue;
+            }
+
+            let mut probability = 1.0;
+            if self.probabilities {
+                let numbers = fields
+                    .iter()
+                    .take_while(|f| f.parse::<f64>().is_ok())
+                    .count();
+                if let Some(p) = fields.first().filter(|_| numbers > 0) {
+                    probability = p.parse().unwrap_or(1.0);
+                }
+                fields.drain(..numbers);
+            }
+
+            let phones = fields
+                .iter()
+                .map(|f| {
+                    f.chars()
+                        .filter(|c| !self.syllable_markers.contains(c))
+                        .collect::<String>()
+                        .to_uppercase()
+                })
+                .filter(|f| !f.is_empty())
+                .collect::<Vec<String>>();
+
+            if phones.is_empty() {
+                return Err(Error::InputError(format!(
+                    "no pronunciation for '{}' in dictionary line '{}'",
+                    word, line
+                )));
+            }
+
+            validate_phones(word, &phones)?;
+
+            if let Some(key) = word.split('(').next() {
+                dict.entry(key.to_lowercase()).or_default().push(Entry {
+                    probability,
+                    phones,
+                    annotation,
+                });
+            }
+        }
+
+        let mut annotations = Annotations::new();
+        let dict = dict
+            .into_iter()
+            .map(|(word, mut pronunciations)| {
+                pronunciations.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+
+                if pronunciations.iter().any(|p| p.annotation.is_some()) {
+                    annotations.insert(
+                        word.clone(),
+                        pronunciations
+                            .iter()
+                            .map(|p| p.annotation.clone())
+                            .collect(),
+                    );
+                }
+
+                (word, pronunciations.into_iter().map(|p| p.phones).collect())
+            })
+            .collect();
+
+        Ok((dict, annotations))
+    }
+}
+
+struct Entry {
+    probability: f64,
+    phones: Vec<String>,
+    annotation: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phones(p: &str) -> Vec<String> {
+        p.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn cmudict_layout() {
+        let (dict, annotations) = PronouncingDict::default()
+            .parse(";;; comment\nREAD  R EH1 D\nread(2) R IY1 D\n\n".as_bytes())
+            .unwrap();
+        assert!(annotations.is_empty());
+        assert_eq!(
+            dict.get("read"),
+            Some(&vec![phones("R EH1 D"), phones("R IY1 D")])
+        );
+    }
+
+    #[test]
+    fn tabs_and_probabilities() {
+        let layout = PronouncingDict {
+            tab_separated: true,
+            probabilities: true,
+            ..PronouncingDict::default()
+        };
+        let (dict, _) = layout
+            .parse(
+                "new york\t0.7\t0.1\t1.0\t1.0\tN UW1 Y AO1 R K\nnew york\t0.9\tN Y UW1 Y AO1 R K\nfar\tF AA1 R\n"
+                    .as_bytes(),
+            )
+            .unwrap();
+        assert_eq!(
+            dict.get("new york"),
+            Some(&vec![
+                phones("N Y UW1 Y AO1 R K"),
+                phones("N UW1 Y AO1 R K")
+            ])
+        );
+        assert_eq!(dict.get("far"), Some(&vec![phones("F AA1 R")]));
+    }
+
+    #[test]
+    fn syllable_markers() {
+        let layout = PronouncingDict {
+            syllable_markers: vec!['-', '.'],
+            ..PronouncingDict::default()
+        };
+        let (dict, _) = layout
+            .parse("banana b ah0 - n ae1 . n ah0\nbanana(2) B AH0- N AE1- N AH0\n".as_bytes())
+            .unwrap();
+        assert_eq!(
+            dict.get("banana"),
+            Some(&vec![
+                phones("B AH0 N AE1 N AH0"),
+                phones("B AH0 N AE1 N AH0")
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(PronouncingDict::default()
+            .parse("banana B AH0 - N AE1 - N AH0\n".as_bytes())
+            .is_err());
+        let probabilities = PronouncingDict {
+            probabilities: true,
+            ..PronouncingDict::default()
+        };
+        assert!(probabilities.parse("far 0.5\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use phoneme::{Phone, Phoneme, Pronunciation, Stress};
+
+/// How two pronunciations rhyme, compared phoneme by phoneme from the last
+/// stressed vowel onward (the rhyming part). Stress digits are ignored once
+/// the rhyming part is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RhymeKind {
+    /// The rhyming parts and the consonants leading into them match,
+    /// e.g. "leave" and "believe".
+    Identical,
+    /// The rhyming parts match, e.g. "far" and "tar".
+    Perfect,
+    /// The vowels match and the consonants that differ share a manner of
+    /// articulation and voicing, e.g. "cup" and "cut".
+    Family,
+    /// The second word adds consonants to the first's rhyming part,
+    /// e.g. "miss" and "mist".
+    Additive,
+    /// The second word drops consonants from the first's rhyming part,
+    /// e.g. "mist" and "miss".
+    Subtractive,
+    /// The vowels match but the consonants don't, e.g. "lake" and "fade".
+    Assonance,
+    /// The consonants match but the vowels don't, e.g. "blank" and "think".
+    Consonance,
+    /// Only the final syllables match, e.g. "cleaver" and "silver".
+    Slant,
+    None,
+}
+
+impl RhymeKind {
+    // Used to pick the closest rhyme over several pronunciations.
+    fn strength(self) -> u8 {
+        match self {
+            RhymeKind::Identical => 8,
+            RhymeKind::Perfect => 7,
+            RhymeKind::Family => 6,
+            RhymeKind::Additive => 5,
+            RhymeKind::Subtractive => 5,
+            RhymeKind::Assonance => 4,
+            RhymeKind::Consonance => 3,
+            RhymeKind::Slant => 2,
+            RhymeKind::None => 0,
+        }
+    }
+
+    /// The closer of two kinds of rhyme.
+    pub fn closest(self, other: RhymeKind) -> RhymeKind {
+        if other.strength() > self.strength() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// Classify how two pronunciations rhyme.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::phoneme::Pronunciation;
+/// use ttaw::rhyme::{classify, RhymeKind};
+///
+/// let cleaver: Pronunciation = "K L IY1 V ER0".parse().unwrap();
+/// let silver: Pronunciation = "S IH1 L V ER0".parse().unwrap();
+/// assert_eq!(classify(&cleaver, &silver), RhymeKind::Slant);
+/// ```
+pub fn classify(a: &Pronunciation, b: &Pronunciation) -> RhymeKind {
+    let (a, b) = (a.phones(), b.phones());
+
+    let (ia, ib) = match (stressed_vowel(a), stressed_vowel(b)) {
+        (Some(ia), Some(ib)) => (ia, ib),
+        _ => return RhymeKind::None,
+    };
+
+    let tail_a = phonemes(&a[ia..]);
+    let tail_b = phonemes(&b[ib..]);
+
+    if tail_a == tail_b {
+        if onset(a, ia) == onset(b, ib) {
+            return RhymeKind::Identical;
+        }
+        return RhymeKind::Perfect;
+    }
+
+    if family(&tail_a, &tail_b) {
+        return RhymeKind::Family;
+    }
+
+    if adds_consonants(&tail_a, &tail_b) {
+        return RhymeKind::Additive;
+    }
+
+    if adds_consonants(&tail_b, &tail_a) {
+        return RhymeKind::Subtractive;
+    }
+
+    let (vowels_a, consonants_a): (Vec<Phoneme>, Vec<Phoneme>) =
+        tail_a.iter().partition(|p| p.is_vowel());
+    let (vowels_b, consonants_b): (Vec<Phoneme>, Vec<Phoneme>) =
+        tail_b.iter().partition(|p| p.is_vowel());
+
+    if vowels_a == vowels_b {
+        return RhymeKind::Assonance;
+    }
+
+    if !consonants_a.is_empty() && consonants_a == consonants_b {
+        return RhymeKind::Consonance;
+    }
+
+    if last_syllable(&tail_a) == last_syllable(&tail_b) {
+        return RhymeKind::Slant;
+    }
+
+    RhymeKind::None
+}
+
+// Index of the last vowel with primary or secondary stress, falling back to
+// the last vowel for pronunciations without stress markers.
+fn stressed_vowel(phones: &[Phone]) -> Option<usize> {
+    phones
+        .iter()
+        .rposition(|p| p.stress == Some(Stress::Primary) || p.stress == Some(Stress::Secondary))
+        .or_else(|| phones.iter().rposition(|p| p.is_vowel()))
+}
+
+// The consonants leading into the vowel at `i`.
+fn onset(phones: &[Phone], i: usize) -> Vec<Phoneme> {
+    let start = phones[..i]
+        .iter()
+        .rposition(|p| p.is_vowel())
+        .map_or(0, |v| v + 1);
+    phonemes(&phones[start..i])
+}
+
+fn phonemes(phones: &[Phone]) -> Vec<Phoneme> {
+    phones.iter().map(|p| p.phoneme).collect()
+}
+
+fn family(a: &[Phoneme], b: &[Phoneme]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b.iter()).all(|(a, b)| {
+            a == b
+                || (a.is_consonant()
+                    && b.is_consonant()
+                    && a.manner() == b.manner()
+                    && a.is_voiced() == b.is_voiced())
+        })
+}
+
+// Whether `long` is `short` with consonants inserted.
+fn adds_consonants(short: &[Phoneme], long: &[Phoneme]) -> bool {
+    if long.len() <= short.len() {
+        return false;
+    }
+
+    let mut remaining = short.iter().peekable();
+    for p in long {
+        if remaining.peek() == Some(&p) {
+            remaining.next();
+        } else if p.is_vowel() {
+            return false;
+        }
+    }
+
+    remaining.peek().is_none()
+}
+
+fn last_syllable(phonemes: &[Phoneme]) -> &[Phoneme] {
+    match phonemes.iter().rposition(|p| p.is_vowel()) {
+        Some(i) => &phonemes[i..],
+        None => phonemes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(a: &str, b: &str) -> RhymeKind {
+        classify(&a.parse().unwrap(), &b.parse().unwrap())
+    }
+
+    #[test]
+    fn kinds() {
+        // leave, believe
+        assert_eq!(kind("L IY1 V", "B IH0 L IY1 V"), RhymeKind::Identical);
+        // far, tar
+        assert_eq!(kind("F AA1 R", "T AA1 R"), RhymeKind::Perfect);
+        // tall, baseball, secondary stress rhymes with primary
+        assert_eq!(kind("T AO1 L", "B EY1 S B AO2 L"), RhymeKind::Perfect);
+        // cup, cut
+        assert_eq!(kind("K AH1 P", "K AH1 T"), RhymeKind::Family);
+        // miss, mist
+        assert_eq!(kind("M IH1 S", "M IH1 S T"), RhymeKind::Additive);
+        // mist, miss
+        assert_eq!(kind("M IH1 S T", "M IH1 S"), RhymeKind::Subtractive);
+        // bed, bend
+        assert_eq!(kind("B EH1 D", "B EH1 N D"), RhymeKind::Additive);
+        // lake, fate
+        assert_eq!(kind("L EY1 K", "F EY1 T"), RhymeKind::Family);
+        // lake, fade
+        assert_eq!(kind("L EY1 K", "F EY1 D"), RhymeKind::Assonance);
+        // blank, think
+        assert_eq!(kind("B L AE1 NG K", "TH IH1 NG K"), RhymeKind::Consonance);
+        // cleaver, silver
+        assert_eq!(kind("K L IY1 V ER0", "S IH1 L V ER0"), RhymeKind::Slant);
+        // shopping, cart
+        assert_eq!(kind("SH AA1 P IH0 NG", "K AA1 R T"), RhymeKind::None);
+    }
+
+    #[test]
+    fn unstressed() {
+        assert_eq!(kind("AH0", "AH0"), RhymeKind::Identical);
+        assert_eq!(kind("HH M", "HH M"), RhymeKind::None);
+    }
+
+    #[test]
+    fn closest() {
+        assert_eq!(
+            RhymeKind::Slant.closest(RhymeKind::Perfect),
+            RhymeKind::Perfect
+        );
+        assert_eq!(
+            RhymeKind::Family.closest(RhymeKind::None),
+            RhymeKind::Family
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use encoder::{self, letters, PhoneticEncoder};
+use error::Error;
+
+/// American Soundex, as used by the US census: the first letter followed by
+/// digits for the consonants after it, padded with zeros or cut to `length`.
+/// Consonants coded the same count once when they're next to each other or
+/// separated only by H or W, vowels in between keep them apart.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::soundex::Soundex;
+///
+/// assert_eq!(Soundex::default().code("Ashcraft"), "A261");
+/// assert_eq!(Soundex { length: 6 }.code("Ashcraft"), "A26130");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Soundex {
+    /// Number of characters in a code, the first letter included.
+    pub length: usize,
+}
+
+/// Refined Soundex, which splits the letters into more groups than American
+/// Soundex and codes vowels too, so codes are longer and collide less. The
+/// first letter is kept and also coded.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::soundex::RefinedSoundex;
+///
+/// assert_eq!(RefinedSoundex::default().code("testing"), "T6036084");
+/// assert_eq!(RefinedSoundex { length: Some(4) }.code("testing"), "T603");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RefinedSoundex {
+    /// Cut codes to this many characters, the first letter included. Codes
+    /// aren't padded.
+    pub length: Option<usize>,
+}
+
+/// Daitch–Mokotoff Soundex, designed for Eastern European and Jewish surnames.
+/// Letters and letter groups are coded by whether they start the name, come
+/// before a vowel or anywhere else, and ambiguous ones like CH (a K sound or
+/// a TCH sound) branch, so a name can have several six digit codes, see
+/// `daitch_mokotoff`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DaitchMokotoff;
+
+/// Number of digits in a Daitch–Mokotoff code.
+const DM_LENGTH: usize = 6;
+
+/// Daitch–Mokotoff rules: the letter groups, then their codes at the start of
+/// a name, before a vowel and anywhere else. `|` separates the codes of a
+/// branching group and an empty code means the group isn't coded.
+const DM_RULES: &[(&[&str], [&str; 3])] = &[
+    (&["AI", "AJ", "AY"], ["0", "1", ""]),
+    (&["AU"], ["0", "7", ""]),
+    (&["A"], ["0", "", ""]),
+    (&["B"], ["7", "7", "7"]),
+    (&["CHS"], ["5", "54", "54"]),
+    (&["CH"], ["5|4", "5|4", "5|4"]),
+    (&["CK"], ["5|45", "5|45", "5|45"]),
+    (&["CZ", "CS", "CSZ", "CZS"], ["4", "4", "4"]),
+    (&["C"], ["5|4", "5|4", "5|4"]),
+    (
+        &["DRZ", "DRS", "DS", "DSH", "DSZ", "DZ", "DZH", "DZS"],
+        ["4", "4", "4"],
+    ),
+    (&["D", "DT"], ["3", "3", "3"]),
+    (&["EI", "EJ", "EY"], ["0", "1", ""]),
+    (&["EU"], ["1", "1", ""]),
+    (&["E"], ["0", "", ""]),
+    (&["F", "FB"], ["7", "7", "7"]),
+    (&["G"], ["5", "5", "5"]),
+    (&["H"], ["5", "5", ""]),
+    (&["IA", "IE", "IO", "IU"], ["1", "", ""]),
+    (&["I"], ["0", "", ""]),
+    (&["J"], ["1|4", "|4", "|4"]),
+    (&["KS"], ["5", "54", "54"]),
+    (&["K", "KH"], ["5", "5", "5"]),
+    (&["L"], ["8", "8", "8"]),
+    (&["MN", "NM"], ["66", "66", "66"]),
+    (&["M", "N"], ["6", "6", "6"]),
+    (&["OI", "OJ", "OY"], ["0", "1", ""]),
+    (&["O"], ["0", "", ""]),
+    (&["P", "PF", "PH"], ["7", "7", "7"]),
+    (&["Q"], ["5", "5", "5"]),
+    (&["R"], ["9", "9", "9"]),
+    (&["RZ", "RS"], ["94|4", "94|4", "94|4"]),
+    (
+        &[
+            "SCHTSCH", "SCHTSH", "SCHTCH", "SHTCH", "SHCH", "SHTSH", "STCH", "STSCH", "SC", "STRZ",
+            "STRS", "STSH", "SZCZ", "SZCS",
+        ],
+        ["2", "4", "4"],
+    ),
+    (&["SCH", "SH", "SZ", "S"], ["4", "4", "4"]),
+    (
+        &["SHT", "SCHT", "SCHD", "ST", "SZT", "SHD", "SZD", "SD"],
+        ["2", "43", "43"],
+    ),
+    (
+        &[
+            "TCH", "TTCH", "TTSCH", "TRZ", "TRS", "TSCH", "TSH", "TS", "TTS", "TTSZ", "TC", "TZ",
+            "TTZ", "TZS", "TSZ",
+        ],
+        ["4", "4", "4"],
+    ),
+    (&["T", "TH"], ["3", "3", "3"]),
+    (&["UI", "UJ", "UY"], ["0", "1", ""]),
+    (&["U", "UE"], ["0", "", ""]),
+    (&["V", "W"], ["7", "7", "7"]),
+    (&["X"], ["5", "54", "54"]),
+    (&["Y"], ["1", "", ""]),
+    (&["ZDZ", "ZDZH", "ZHDZH"], ["2", "4", "4"]),
+    (&["ZD", "ZHD"], ["2", "43", "43"]),
+    (&["ZH", "ZS", "ZSCH", "ZSH", "Z"], ["4", "4", "4"]),
+];
+
+impl Default for Soundex {
+    fn default() -> Soundex {
+        Soundex { length: 4 }
+    }
+}
+
+impl Soundex {
+    /// The Soundex code for `w`, empty if it has no Latin letters.
+    pub fn code(&self, w: &str) -> String {
+        let letters = letters(w);
+        let first = match letters.first() {
+            Some(c) => *c,
+            None => return String::new(),
+        };
+
+        let mut code = first.to_string();
+        let mut last = soundex_digit(first);
+
+        for c in &letters[1..] {
+            match *c {
+                // H and W don't separate consonants coded the same
+                'H' | 'W' => continue,
+                c => {
+                    let digit = soundex_digit(c);
+                    if digit != '0' && digit != last {
+                        code.push(digit);
+                    }
+                    last = digit;
+                }
+            }
+        }
+
+        while code.len() < self.length {
+            code.push('0');
+        }
+        code.truncate(self.length);
+        code
+    }
+}
+
+impl RefinedSoundex {
+    /// The Refined Soundex code for `w`, empty if it has no Latin letters.
+    pub fn code(&self, w: &str) -> String {
+        let letters = letters(w);
+        let first = match letters.first() {
+            Some(c) => *c,
+            None => return String::new(),
+        };
+
+        let mut code = first.to_string();
+        let mut last = None;
+
+        for c in &letters {
+            let digit = refined_digit(*c);
+            if Some(digit) != last {
+                code.push(digit);
+            }
+            last = Some(digit);
+        }
+
+        if let Some(length) = self.length {
+            code.truncate(length);
+        }
+        code
+    }
+}
+
+/// American Soundex code for `w`, four characters long.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::soundex::soundex;
+///
+/// assert_eq!(soundex("Robert"), "R163");
+/// assert_eq!(soundex("Rupert"), "R163");
+/// assert_eq!(soundex("Tymczak"), "T522");
+/// ```
+pub fn soundex(w: &str) -> String {
+    Soundex::default().code(w)
+}
+
+/// Every Daitch–Mokotoff code for `w`, six digits each, the first branch of
+/// each ambiguous letter group first. Empty if `w` has no Latin letters.
+///
+/// The longest letter group starting at each position is coded. A code isn't
+/// repeated when the group before it ended in the same code, unless a vowel
+/// came in between, except for M and N next to each other.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::soundex::daitch_mokotoff;
+///
+/// assert_eq!(daitch_mokotoff("Moskowitz"), vec!["645740"]);
+/// assert_eq!(daitch_mokotoff("Auerbach"), vec!["097500", "097400"]);
+/// assert_eq!(daitch_mokotoff("Auerbach"), daitch_mokotoff("Ohrbach"));
+/// ```
+pub fn daitch_mokotoff(w: &str) -> Vec<String> {
+    let letters = letters(w).into_iter().collect::<String>();
+    if letters.is_empty() {
+        return vec![];
+    }
+
+    // each branch's code so far and the last group's code
+    let mut branches: Vec<(String, Option<&str>)> = vec![(String::new(), None)];
+    let mut pos = 0;
+
+    while pos < letters.len() {
+        let rest = &letters[pos..];
+        let (group, codes) = match DM_RULES
+            .iter()
+            .flat_map(|(groups, codes)| groups.iter().map(move |g| (*g, codes)))
+            .filter(|(g, _)| rest.starts_with(g))
+            .max_by_key(|(g, _)| g.len())
+        {
+            Some(rule) => rule,
+            None => break,
+        };
+
+        let before_vowel = rest[group.len()..].starts_with(|c| "AEIOU".contains(c));
+        let codes = if pos == 0 {
+            codes[0]
+        } else if before_vowel {
+            codes[1]
+        } else {
+            codes[2]
+        };
+        let repeat = group == "MN" || group == "NM";
+
+        // a branch stops growing at six digits, and finished branches with
+        // the same code are merged, otherwise every ambiguous group would
+        // double the branches however long the name is
+        let mut next: Vec<(String, Option<&str>)> = vec![];
+        for (code, last) in &branches {
+            if code.len() == DM_LENGTH {
+                let branch = (code.clone(), None);
+                if !next.contains(&branch) {
+                    next.push(branch);
+                }
+                continue;
+            }
+
+            for alternative in codes.split('|') {
+                let mut code = code.clone();
+                if repeat || !last.is_some_and(|l| l.ends_with(alternative)) {
+                    code.push_str(alternative);
+                }
+                code.truncate(DM_LENGTH);
+
+                let branch = if code.len() == DM_LENGTH {
+                    (code, None)
+                } else {
+                    (code, Some(alternative))
+                };
+                if !next.contains(&branch) {
+                    next.push(branch);
+                }
+            }
+        }
+
+        branches = next;
+        pos += group.len();
+
+        if branches.iter().all(|(code, _)| code.len() == DM_LENGTH) {
+            break;
+        }
+    }
+
+    let mut codes: Vec<String> = vec![];
+    for (mut code, _) in branches {
+        while code.len() < DM_LENGTH {
+            code.push('0');
+        }
+
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+
+    codes
+}
+
+/// Refined Soundex code for `w`, as long as the word needs.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::soundex::refined_soundex;
+///
+/// assert_eq!(refined_soundex("Braz"), "B1905");
+/// assert_eq!(refined_soundex("Broz"), "B1905");
+/// ```
+pub fn refined_soundex(w: &str) -> String {
+    RefinedSoundex::default().code(w)
+}
+
+impl PhoneticEncoder for Soundex {
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        Ok(encoder::single_code(self.code(w)))
+    }
+
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(encoder::codes_rhyme(&self.code(a), &self.code(b)))
+    }
+
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(encoder::codes_alliterate(&self.code(a), &self.code(b)))
+    }
+}
+
+impl PhoneticEncoder for RefinedSoundex {
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        Ok(encoder::single_code(self.code(w)))
+    }
+
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(encoder::codes_rhyme(&self.code(a), &self.code(b)))
+    }
+
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(encoder::codes_alliterate(&self.code(a), &self.code(b)))
+    }
+}
+
+impl PhoneticEncoder for DaitchMokotoff {
+    fn encode(&self, w: &str) -> Result<Option<Vec<String>>, Error> {
+        let codes = daitch_mokotoff(w);
+        Ok(if codes.is_empty() { None } else { Some(codes) })
+    }
+
+    fn rhyme(&self, a: &str, b: &str) -> Result<bool, Error> {
+        let (a, b) = (daitch_mokotoff(a), daitch_mokotoff(b));
+        Ok(a.iter()
+            .any(|a| b.iter().any(|b| encoder::codes_rhyme(a, b))))
+    }
+
+    fn alliterate(&self, a: &str, b: &str) -> Result<bool, Error> {
+        let (a, b) = (daitch_mokotoff(a), daitch_mokotoff(b));
+        Ok(a.iter()
+            .any(|a| b.iter().any(|b| encoder::codes_alliterate(a, b))))
+    }
+}
+
+// '0' for the vowels, H, W and Y, which aren't coded.
+fn soundex_digit(c: char) -> char {
+    match c {
+        'B' | 'F' | 'P' | 'V' => '1',
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => '2',
+        'D' | 'T' => '3',
+        'L' => '4',
+        'M' | 'N' => '5',
+        'R' => '6',
+        _ => '0',
+    }
+}
+
+fn refined_digit(c: char) -> char {
+    match c {
+        'B' | 'P' => '1',
+        'F' | 'V' => '2',
+        'C' | 'K' | 'S' => '3',
+        'G' | 'J' => '4',
+        'Q' | 'X' | 'Z' => '5',
+        'D' | 'T' => '6',
+        'L' => '7',
+        'M' | 'N' => '8',
+        'R' => '9',
+        _ => '0',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separators() {
+        // the S and C in "Ashcraft" are coded once across the H
+        assert_eq!(soundex("Ashcraft"), "A261");
+        // but twice across a vowel
+        assert_eq!(soundex("Tymczak"), "T522");
+        // the first letter counts as coded
+        assert_eq!(soundex("Pfister"), "P236");
+    }
+
+    #[test]
+    fn no_letters() {
+        assert_eq!(soundex(""), "");
+        assert_eq!(soundex("42"), "");
+        assert_eq!(refined_soundex(" - "), "");
+        assert_eq!(Soundex::default().encode("").unwrap(), None);
+    }
+}
@@ -0,0 +1,128 @@
+use error::Error;
+#[cfg(feature = "reqwest")]
+use reqwest;
+use std::fs;
+use std::path::PathBuf;
+
+/// Where `CmuDict` downloads the dictionary from unless told otherwise.
+pub const CMUDICT_URL: &str =
+    "https://raw.githubusercontent.com/cmusphinx/cmudict/master/cmudict.dict";
+
+/// Somewhere a dictionary in the upstream cmudict.dict text format can be
+/// fetched from.
+///
+/// Implemented for an HTTP URL (with the `reqwest` feature), a local file,
+/// in-memory bytes, and any closure returning the dictionary bytes.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::cmu::CmuDict;
+/// use ttaw::source::DictionarySource;
+///
+/// let source = || Ok(b"far F AA1 R\ntar T AA1 R\n".to_vec());
+/// assert_eq!(source.fetch().unwrap(), b"far F AA1 R\ntar T AA1 R\n".to_vec());
+///
+/// let cmudict = CmuDict::from_source(&source).unwrap();
+/// assert!(cmudict.rhyme("far", "tar").unwrap());
+/// ```
+pub trait DictionarySource {
+    /// The raw dictionary text.
+    fn fetch(&self) -> Result<Vec<u8>, Error>;
+
+    /// Where the dictionary comes from, recorded alongside serialized copies.
+    fn location(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Download the dictionary over HTTP, by default from the upstream
+/// cmusphinx repository.
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpSource {
+    url: String,
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpSource {
+    /// Download from `url` instead, e.g. an internal mirror.
+    pub fn new(url: &str) -> HttpSource {
+        HttpSource {
+            url: url.to_string(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Default for HttpSource {
+    fn default() -> HttpSource {
+        HttpSource::new(CMUDICT_URL)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl DictionarySource for HttpSource {
+    fn fetch(&self) -> Result<Vec<u8>, Error> {
+        let response = reqwest::blocking::get(self.url.as_str())?.error_for_status()?;
+        Ok(response.bytes()?.to_vec())
+    }
+
+    fn location(&self) -> Option<String> {
+        Some(self.url.clone())
+    }
+}
+
+/// Read the dictionary from a file on disk, e.g. a vendored cmudict.dict.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSource {
+    path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(path: &str) -> FileSource {
+        FileSource {
+            path: PathBuf::from(path),
+        }
+    }
+}
+
+impl DictionarySource for FileSource {
+    fn fetch(&self) -> Result<Vec<u8>, Error> {
+        Ok(fs::read(&self.path)?)
+    }
+
+    fn location(&self) -> Option<String> {
+        Some(self.path.display().to_string())
+    }
+}
+
+/// Dictionary text that's already in memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BytesSource {
+    bytes: Vec<u8>,
+}
+
+impl BytesSource {
+    pub fn new(bytes: Vec<u8>) -> BytesSource {
+        BytesSource { bytes }
+    }
+}
+
+impl DictionarySource for BytesSource {
+    fn fetch(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.bytes.clone())
+    }
+}
+
+impl<F> DictionarySource for F
+where
+    F: Fn() -> Result<Vec<u8>, Error>,
+{
+    fn fetch(&self) -> Result<Vec<u8>, Error> {
+        self()
+    }
+}
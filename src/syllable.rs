@@ -0,0 +1,188 @@
+use phoneme::{Phone, Phoneme, Pronunciation, Stress};
+use std::fmt;
+
+// Consonant clusters English allows at the start of a syllable, besides single
+// consonants other than NG.
+const ONSETS: &[&[Phoneme]] = {
+    use phoneme::Phoneme::*;
+    &[
+        &[P, R],
+        &[P, L],
+        &[P, Y],
+        &[B, R],
+        &[B, L],
+        &[B, Y],
+        &[T, R],
+        &[T, W],
+        &[D, R],
+        &[D, W],
+        &[K, R],
+        &[K, L],
+        &[K, W],
+        &[K, Y],
+        &[G, R],
+        &[G, L],
+        &[G, W],
+        &[F, R],
+        &[F, L],
+        &[F, Y],
+        &[V, Y],
+        &[TH, R],
+        &[TH, W],
+        &[SH, R],
+        &[HH, Y],
+        &[M, Y],
+        &[S, P],
+        &[S, T],
+        &[S, K],
+        &[S, F],
+        &[S, M],
+        &[S, N],
+        &[S, L],
+        &[S, W],
+        &[S, P, R],
+        &[S, P, L],
+        &[S, P, Y],
+        &[S, T, R],
+        &[S, K, R],
+        &[S, K, L],
+        &[S, K, W],
+        &[S, K, Y],
+    ]
+};
+
+/// A syllable: the vowel at its core, and the consonants before and after it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Syllable {
+    pub onset: Vec<Phone>,
+    pub nucleus: Phone,
+    pub coda: Vec<Phone>,
+}
+
+impl Syllable {
+    /// The syllable's phones in order.
+    pub fn phones(&self) -> Vec<Phone> {
+        let mut phones = self.onset.clone();
+        phones.push(self.nucleus);
+        phones.extend_from_slice(&self.coda);
+        phones
+    }
+
+    pub fn stress(&self) -> Option<Stress> {
+        self.nucleus.stress
+    }
+}
+
+impl fmt::Display for Syllable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let phones = self
+            .phones()
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}", phones.join(" "))
+    }
+}
+
+/// Split a pronunciation into syllables, one per vowel. The consonants between
+/// two vowels go to the second syllable's onset, as many of them as English
+/// allows to start a syllable (the maximum onset principle), the rest to the
+/// first syllable's coda. A pronunciation without vowels has no syllables.
+///
+/// ```rust
+/// extern crate ttaw;
+/// use ttaw::phoneme::Pronunciation;
+/// use ttaw::syllable::syllabify;
+///
+/// let instruct: Pronunciation = "IH0 N S T R AH1 K T".parse().unwrap();
+/// let syllables = syllabify(&instruct);
+/// assert_eq!(syllables[0].to_string(), "IH0 N");
+/// assert_eq!(syllables[1].to_string(), "S T R AH1 K T");
+/// assert_eq!(syllables[1].onset.len(), 3);
+/// ```
+pub fn syllabify(pronunciation: &Pronunciation) -> Vec<Syllable> {
+    let phones = pronunciation.phones();
+    let vowels: Vec<usize> = phones
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.is_vowel())
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut syllables: Vec<Syllable> = vec![];
+    let mut start = 0;
+
+    for (n, vowel) in vowels.iter().enumerate() {
+        let end = match vowels.get(n + 1) {
+            Some(next) => vowel + 1 + onset_start(&phones[vowel + 1..*next]),
+            None => phones.len(),
+        };
+
+        syllables.push(Syllable {
+            onset: phones[start..*vowel].to_vec(),
+            nucleus: phones[*vowel],
+            coda: phones[vowel + 1..end].to_vec(),
+        });
+        start = end;
+    }
+
+    syllables
+}
+
+// Where the longest legal onset starts in the consonants between two vowels.
+fn onset_start(consonants: &[Phone]) -> usize {
+    (0..consonants.len())
+        .find(|i| is_onset(&consonants[*i..]))
+        .unwrap_or(consonants.len())
+}
+
+fn is_onset(phones: &[Phone]) -> bool {
+    let phonemes: Vec<Phoneme> = phones.iter().map(|p| p.phoneme).collect();
+
+    match phonemes.as_slice() {
+        [] => true,
+        [p] => *p != Phoneme::NG,
+        cluster => ONSETS.contains(&cluster),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(phones: &str) -> Vec<String> {
+        syllabify(&phones.parse().unwrap())
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn maximum_onset() {
+        assert_eq!(split("B AH0 N AE1 N AH0"), vec!["B AH0", "N AE1", "N AH0"]);
+        assert_eq!(
+            split("EH2 K S P L AH0 N EY1 SH AH0 N"),
+            vec!["EH2 K", "S P L AH0", "N EY1", "SH AH0 N"]
+        );
+        // "ŋ" can't start a syllable, nor can "nt"
+        assert_eq!(split("S IH1 NG ER0"), vec!["S IH1 NG", "ER0"]);
+        assert_eq!(split("W IH1 N T ER0"), vec!["W IH1 N", "T ER0"]);
+        assert_eq!(split("S T R EH1 NG K TH S"), vec!["S T R EH1 NG K TH S"]);
+    }
+
+    #[test]
+    fn parts() {
+        let syllables = syllabify(&"S T R EH1 NG K TH S".parse().unwrap());
+        assert_eq!(syllables.len(), 1);
+        assert_eq!(syllables[0].onset.len(), 3);
+        assert_eq!(syllables[0].nucleus, "EH1".parse().unwrap());
+        assert_eq!(syllables[0].coda.len(), 4);
+        assert_eq!(syllables[0].stress(), Some(Stress::Primary));
+    }
+
+    #[test]
+    fn no_vowels() {
+        assert!(syllabify(&"HH M".parse().unwrap()).is_empty());
+        assert!(syllabify(&Pronunciation::new(vec![])).is_empty());
+    }
+}
@@ -1,9 +1,13 @@
+extern crate memmap2;
 extern crate ttaw;
 
 #[cfg(test)]
 extern crate tempfile;
 
-use ttaw::cmu::CmuDict;
+use std::fs;
+use ttaw::cmu::{AlliterationOptions, CmuDict, Encoding, HomophoneOptions, Query, RhymeOptions};
+use ttaw::phoneme::{Phone, Phoneme, Pronunciation, Stress};
+use ttaw::rhyme::RhymeKind;
 
 #[test]
 fn cmu_encoding_found() {
@@ -131,3 +135,832 @@ fn quick_brown_fox() {
     assert!(!cmudict.alliteration("a", "lazy").unwrap());
     assert!(!cmudict.alliteration("lazy", "dog").unwrap());
 }
+
+#[test]
+fn from_dict_file() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.encoding("unearthed"),
+        Ok(Some(vec![vec![
+            "AH0".to_string(),
+            "N".to_string(),
+            "ER1".to_string(),
+            "TH".to_string(),
+            "T".to_string()
+        ]]))
+    );
+    assert_eq!(
+        cmudict.encoding("a"),
+        Ok(Some(vec![vec!["AH0".to_string()], vec!["EY1".to_string()]]))
+    );
+    assert!(cmudict.rhyme("far", "tar").unwrap());
+    assert!(cmudict.rhyme("a", "say").unwrap());
+    assert!(cmudict.alliteration("bouncing", "bears").unwrap());
+    assert!(!cmudict.alliteration("lazy", "dog").unwrap());
+}
+
+#[test]
+fn from_dict_file_missing() {
+    assert!(CmuDict::from_dict_file("tests/fixtures/does-not-exist.dict").is_err());
+}
+
+#[test]
+fn compact_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.bin");
+    let path = path.to_str().unwrap();
+
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    cmudict.write_compact(path).unwrap();
+
+    let compact = CmuDict::from_compact_file(path).unwrap();
+    for word in &["a", "read", "permeability", "unearthed", "far", "tar"] {
+        assert_eq!(compact.encoding(word), cmudict.encoding(word));
+    }
+    assert_eq!(compact.encoding("zebra"), Ok(None));
+    assert!(compact.rhyme("far", "tar").unwrap());
+    assert!(compact.alliteration("bouncing", "bears").unwrap());
+
+    // and back out again, byte for byte
+    assert_eq!(compact.to_compact().unwrap(), fs::read(path).unwrap());
+}
+
+#[test]
+fn compact_memory_mapped() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.bin");
+
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    cmudict.write_compact(path.to_str().unwrap()).unwrap();
+
+    let file = fs::File::open(&path).unwrap();
+    let mmap = unsafe { memmap2::Mmap::map(&file).unwrap() };
+    let compact = CmuDict::from_compact_bytes(mmap).unwrap();
+    assert_eq!(compact.encoding("read"), cmudict.encoding("read"));
+    assert!(compact.rhyme("a", "say").unwrap());
+}
+
+#[test]
+fn compact_not_compact() {
+    assert!(CmuDict::from_compact_file("tests/fixtures/cmudict.dict").is_err());
+}
+
+#[cfg(feature = "embedded")]
+#[test]
+fn embedded() {
+    let cmudict = CmuDict::embedded().unwrap();
+    assert!(cmudict.rhyme("far", "tar").unwrap());
+    assert!(cmudict.alliteration("bouncing", "bears").unwrap());
+    assert_eq!(
+        cmudict.encoding("unearthed"),
+        Ok(Some(vec![vec![
+            "AH0".to_string(),
+            "N".to_string(),
+            "ER1".to_string(),
+            "TH".to_string(),
+            "T".to_string()
+        ]]))
+    );
+    assert_eq!(
+        cmudict.annotations("d'artagnan"),
+        Some(vec![Some("foreign french".to_string())])
+    );
+}
+
+#[test]
+fn pronunciations() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.pronunciations("read"),
+        Ok(Some(vec![
+            Pronunciation::new(vec![
+                Phone::new(Phoneme::R, None),
+                Phone::new(Phoneme::EH, Some(Stress::Primary)),
+                Phone::new(Phoneme::D, None),
+            ]),
+            Pronunciation::new(vec![
+                Phone::new(Phoneme::R, None),
+                Phone::new(Phoneme::IY, Some(Stress::Primary)),
+                Phone::new(Phoneme::D, None),
+            ]),
+        ]))
+    );
+    assert_eq!(cmudict.pronunciations("zebra"), Ok(None));
+}
+
+#[test]
+fn invalid_phones_rejected() {
+    assert!(CmuDict::from_dict_reader("far F AA1 R\ntar T XX1 R\n".as_bytes()).is_err());
+    assert!(CmuDict::from_dict_reader("far F1 AA1 R\n".as_bytes()).is_err());
+}
+
+#[test]
+fn syllables() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(cmudict.syllables("permeability"), Ok(Some(vec![6])));
+    assert_eq!(cmudict.syllables("a"), Ok(Some(vec![1, 1])));
+    assert_eq!(cmudict.syllables("Lazy "), Ok(Some(vec![2])));
+    assert_eq!(cmudict.syllables("zebra"), Ok(None));
+}
+
+#[test]
+fn stress_pattern() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.stress_pattern("permeability"),
+        Ok(Some(vec![vec![0, 2, 0, 1, 0, 0]]))
+    );
+    assert_eq!(
+        cmudict.stress_pattern("a"),
+        Ok(Some(vec![vec![0], vec![1]]))
+    );
+    assert_eq!(
+        cmudict.stress_pattern("unearthed"),
+        Ok(Some(vec![vec![0, 1]]))
+    );
+    assert_eq!(cmudict.stress_pattern("zebra"), Ok(None));
+}
+
+#[test]
+fn rhymes_for() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.rhymes_for("far"),
+        vec!["car", "guitar", "star", "tar"]
+    );
+    assert_eq!(
+        cmudict.rhymes_for(" Tar"),
+        vec!["car", "far", "guitar", "star"]
+    );
+    assert_eq!(cmudict.rhymes_for("read"), vec!["red"]);
+    assert!(cmudict.rhymes_for("zebra").is_empty());
+    assert!(cmudict.rhymes_for("").is_empty());
+}
+
+#[test]
+fn rhymes_for_with_options() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+
+    let one_syllable = RhymeOptions {
+        syllables: Some(1),
+        ..RhymeOptions::default()
+    };
+    assert_eq!(
+        cmudict.rhymes_for_with("far", &one_syllable),
+        vec!["car", "star", "tar"]
+    );
+
+    let all_variants = RhymeOptions {
+        all_variants: true,
+        ..RhymeOptions::default()
+    };
+    assert_eq!(
+        cmudict.rhymes_for_with("read", &all_variants),
+        vec!["bead", "red", "stampede"]
+    );
+    // "read" only rhymes with "bead" on its second pronunciation
+    assert!(!cmudict.rhymes_for("bead").contains(&"read"));
+    assert!(cmudict
+        .rhymes_for_with("bead", &all_variants)
+        .contains(&"read"));
+}
+
+#[test]
+fn rhymes_for_compact() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    let compact = CmuDict::from_compact_bytes(cmudict.to_compact().unwrap()).unwrap();
+    assert_eq!(compact.rhymes_for("far"), cmudict.rhymes_for("far"));
+}
+
+#[test]
+fn rhyme_kind() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(cmudict.rhyme_kind("pair", "pear"), Ok(RhymeKind::Identical));
+    assert_eq!(
+        cmudict.rhyme_kind("leave", "believe"),
+        Ok(RhymeKind::Identical)
+    );
+    assert_eq!(cmudict.rhyme_kind("far", "tar"), Ok(RhymeKind::Perfect));
+    assert_eq!(cmudict.rhyme_kind("Far ", "guitar"), Ok(RhymeKind::Perfect));
+    assert_eq!(cmudict.rhyme_kind("cup", "cut"), Ok(RhymeKind::Family));
+    assert_eq!(cmudict.rhyme_kind("miss", "mist"), Ok(RhymeKind::Additive));
+    assert_eq!(
+        cmudict.rhyme_kind("mist", "miss"),
+        Ok(RhymeKind::Subtractive)
+    );
+    assert_eq!(cmudict.rhyme_kind("lake", "fade"), Ok(RhymeKind::Assonance));
+    assert_eq!(
+        cmudict.rhyme_kind("blank", "think"),
+        Ok(RhymeKind::Consonance)
+    );
+    assert_eq!(
+        cmudict.rhyme_kind("pitter", "patter"),
+        Ok(RhymeKind::Consonance)
+    );
+    assert_eq!(cmudict.rhyme_kind("shopping", "cart"), Ok(RhymeKind::None));
+    assert_eq!(cmudict.rhyme_kind("zebra", "cart"), Ok(RhymeKind::None));
+}
+
+#[test]
+fn rhyme_kind_general_syllabic() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.rhyme_kind("cleaver", "silver"),
+        Ok(RhymeKind::Slant)
+    );
+    assert_eq!(cmudict.rhyme_kind("bottle", "fiddle"), Ok(RhymeKind::Slant));
+}
+
+#[test]
+fn rhyme_kind_variants() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    // "read" rhymes with "bead" on its second pronunciation only
+    assert_eq!(cmudict.rhyme_kind("read", "bead"), Ok(RhymeKind::Perfect));
+    assert_eq!(cmudict.rhyme_kind("read", "red"), Ok(RhymeKind::Identical));
+}
+
+#[test]
+fn alliteration_variants() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    // "herb" alliterates with "hat" on its second pronunciation only
+    assert!(cmudict.alliteration("herb", "hat").unwrap());
+    assert!(cmudict.alliteration("hat", "herb").unwrap());
+}
+
+#[test]
+fn alliteration_phonemes() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    // decided on the sounds, not the spelling
+    assert!(cmudict.alliteration("one", "wet").unwrap());
+    assert!(!cmudict.alliteration("honest", "hat").unwrap());
+    assert!(!cmudict.alliteration("apple", "actor").unwrap());
+    assert!(!cmudict.alliteration("lazy", "dog").unwrap());
+}
+
+#[test]
+fn alliteration_with_options() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert!(cmudict.alliteration("snappy", "sails").unwrap());
+
+    let clusters = AlliterationOptions {
+        clusters: true,
+        ..AlliterationOptions::default()
+    };
+    assert!(cmudict
+        .alliteration_with("snappy", "snails", &clusters)
+        .unwrap());
+    assert!(!cmudict
+        .alliteration_with("snappy", "sails", &clusters)
+        .unwrap());
+    assert!(cmudict
+        .alliteration_with("bouncing", "bears", &clusters)
+        .unwrap());
+
+    let vowels = AlliterationOptions {
+        vowels: true,
+        ..AlliterationOptions::default()
+    };
+    assert!(cmudict
+        .alliteration_with("apple", "actor", &vowels)
+        .unwrap());
+    assert!(!cmudict
+        .alliteration_with("apple", "honest", &vowels)
+        .unwrap());
+    assert!(!cmudict.alliteration_with("apple", "hat", &vowels).unwrap());
+}
+
+#[test]
+fn encoding_or_predict() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.encoding_or_predict("Far ").unwrap(),
+        Some(Encoding {
+            pronunciations: vec![vec!["F".to_string(), "AA1".to_string(), "R".to_string()]],
+            predicted: false,
+        })
+    );
+    assert_eq!(
+        cmudict.encoding_or_predict("rizz").unwrap(),
+        Some(Encoding {
+            pronunciations: vec![vec!["R".to_string(), "IH1".to_string(), "Z".to_string()]],
+            predicted: true,
+        })
+    );
+    assert_eq!(cmudict.encoding_or_predict("!!").unwrap(), None);
+    assert_eq!(cmudict.encoding("rizz").unwrap(), None);
+}
+
+#[test]
+fn predict_missing_words() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert!(!cmudict.rhyme("guitar", "skrrtar").unwrap());
+    assert!(!cmudict.alliteration("bears", "blorf").unwrap());
+
+    let rhyme = RhymeOptions {
+        predict: true,
+        ..RhymeOptions::default()
+    };
+    assert!(cmudict.rhyme_with("guitar", "skrrtar", &rhyme).unwrap());
+    assert!(cmudict.rhymes_for_with("blar", &rhyme).contains(&"far"));
+    assert!(!cmudict.rhyme_with("far", "blorf", &rhyme).unwrap());
+
+    // predicted without a stressed vowel, so like the index nothing rhymes
+    assert!(!cmudict.rhyme_with("brr", "tsk", &rhyme).unwrap());
+    assert!(cmudict.rhymes_for_with("brr", &rhyme).is_empty());
+
+    let alliteration = AlliterationOptions {
+        predict: true,
+        ..AlliterationOptions::default()
+    };
+    assert!(cmudict
+        .alliteration_with("bears", "blorf", &alliteration)
+        .unwrap());
+}
+
+#[test]
+fn rhyme_with_options() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    let first_only = RhymeOptions::default();
+    // "read" rhymes with "bead" on its second pronunciation only
+    assert!(cmudict.rhyme("read", "bead").unwrap());
+    assert!(!cmudict.rhyme_with("read", "bead", &first_only).unwrap());
+
+    let two_syllables = RhymeOptions {
+        syllables: Some(2),
+        all_variants: true,
+        ..RhymeOptions::default()
+    };
+    assert!(cmudict
+        .rhyme_with("read", "stampede", &two_syllables)
+        .unwrap());
+    assert!(!cmudict.rhyme_with("read", "bead", &two_syllables).unwrap());
+}
+
+#[test]
+fn no_stressed_vowel() {
+    let cmudict = CmuDict::from_dict_reader("hmm HH M\nshh SH\n".as_bytes()).unwrap();
+    assert!(!cmudict.rhyme("hmm", "shh").unwrap());
+    assert!(cmudict.rhymes_for("hmm").is_empty());
+}
+
+#[test]
+fn ipa() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.ipa("Permeability").unwrap(),
+        Some(vec!["pɚˌmiəˈbɪlɪti".to_string()])
+    );
+    assert_eq!(
+        cmudict.ipa("read").unwrap(),
+        Some(vec!["ˈɹɛd".to_string(), "ˈɹid".to_string()])
+    );
+    assert_eq!(cmudict.ipa("rizz").unwrap(), None);
+}
+
+#[test]
+fn syllabify() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    let permeability = &cmudict.syllabify("permeability").unwrap().unwrap()[0];
+    assert_eq!(
+        permeability
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>(),
+        vec!["P ER0", "M IY2", "AH0", "B IH1", "L IH0", "T IY0"]
+    );
+    assert_eq!(permeability[3].stress(), Some(Stress::Primary));
+
+    let read = cmudict.syllabify("read").unwrap().unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[1][0].nucleus.phoneme, Phoneme::IY);
+
+    assert_eq!(cmudict.syllabify("rizz").unwrap(), None);
+}
+
+#[test]
+fn overlay_file() {
+    let mut cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(cmudict.encoding("zaphod").unwrap(), None);
+    assert!(cmudict.rhymes_for("far").contains(&"car"));
+
+    cmudict.load_overlay("tests/fixtures/overlay.dict").unwrap();
+
+    // added
+    assert_eq!(cmudict.syllables("Zaphod").unwrap(), Some(vec![2]));
+    assert_eq!(cmudict.syllables("beeblebrox").unwrap(), Some(vec![3]));
+    // replaced, with variants
+    assert_eq!(
+        cmudict.encoding("tar").unwrap(),
+        Some(vec![
+            vec!["T".to_string(), "AA1".to_string(), "R".to_string()],
+            vec!["T".to_string(), "EH1".to_string(), "R".to_string()],
+        ])
+    );
+    // a pronunciation added to the dictionary's
+    assert_eq!(cmudict.encoding("car").unwrap().unwrap().len(), 2);
+    assert!(cmudict.rhyme("car", "pear").unwrap());
+    // removed
+    assert_eq!(cmudict.encoding("dog").unwrap(), None);
+    assert!(!cmudict.alliteration("dog", "dog").unwrap());
+
+    // the rhyme index is rebuilt with the overlay
+    let all_variants = RhymeOptions {
+        all_variants: true,
+        ..RhymeOptions::default()
+    };
+    assert!(cmudict
+        .rhymes_for_with("pear", &all_variants)
+        .contains(&"tar"));
+}
+
+#[test]
+fn overlays_stack() {
+    let mut cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    cmudict.load_overlay("tests/fixtures/overlay.dict").unwrap();
+    cmudict
+        .load_overlay_reader("dog D AO1 G\n-zaphod\n".as_bytes())
+        .unwrap();
+
+    assert_eq!(cmudict.syllables("dog").unwrap(), Some(vec![1]));
+    assert_eq!(cmudict.syllables("zaphod").unwrap(), None);
+}
+
+#[test]
+fn overlay_rejected_whole() {
+    let mut cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert!(cmudict
+        .load_overlay_reader("zaphod Z AE1 F AA0 D\nfoo F XX1\n".as_bytes())
+        .is_err());
+    assert!(cmudict
+        .load_overlay_reader("zaphod Z AE1 F AA0 D\n+foo\n".as_bytes())
+        .is_err());
+    assert_eq!(cmudict.encoding("zaphod").unwrap(), None);
+    assert!(cmudict.load_overlay("tests/fixtures/missing.dict").is_err());
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.rhymes_for("far"),
+        vec!["car", "guitar", "star", "tar"]
+    );
+
+    cmudict
+        .insert(
+            "Bazaar",
+            vec![vec![
+                "B".to_string(),
+                "AH0".to_string(),
+                "Z".to_string(),
+                "AA1".to_string(),
+                "R".to_string(),
+            ]],
+        )
+        .unwrap();
+    assert_eq!(
+        cmudict.remove("star"),
+        Some(vec![vec![
+            "S".to_string(),
+            "T".to_string(),
+            "AA1".to_string(),
+            "R".to_string()
+        ]])
+    );
+    assert_eq!(cmudict.remove("star"), None);
+    assert_eq!(
+        cmudict.rhymes_for("far"),
+        vec!["bazaar", "car", "guitar", "tar"]
+    );
+
+    assert!(cmudict.insert("foo", vec![]).is_err());
+    assert!(cmudict.insert("foo", vec![vec!["XX".to_string()]]).is_err());
+
+    // overlays are kept when serializing
+    let compact = CmuDict::from_compact_bytes(cmudict.to_compact().unwrap()).unwrap();
+    assert_eq!(
+        compact.rhymes_for("far"),
+        vec!["bazaar", "car", "guitar", "tar"]
+    );
+}
+
+#[test]
+fn annotations() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.annotations("Suede"),
+        Some(vec![Some("foreign".to_string())])
+    );
+    assert_eq!(cmudict.annotations("read"), Some(vec![None, None]));
+    assert_eq!(cmudict.annotations("zebra"), None);
+    // the comment isn't taken for phones
+    assert_eq!(cmudict.syllables("ade").unwrap(), Some(vec![1]));
+}
+
+#[test]
+fn rhymes_excluding_annotations() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(cmudict.rhymes_for("fade"), vec!["ade", "braid", "suede"]);
+
+    let options = RhymeOptions {
+        exclude: vec!["foreign".to_string(), "abbrev".to_string()],
+        ..RhymeOptions::default()
+    };
+    assert_eq!(cmudict.rhymes_for_with("fade", &options), vec!["braid"]);
+    assert!(cmudict.rhymes_for_with("suede", &options).is_empty());
+    assert!(cmudict.rhyme_with("fade", "braid", &options).unwrap());
+    assert!(!cmudict.rhyme_with("fade", "suede", &options).unwrap());
+    assert!(cmudict.rhyme("fade", "suede").unwrap());
+}
+
+#[test]
+fn compact_annotations() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    let compact = CmuDict::from_compact_bytes(cmudict.to_compact().unwrap()).unwrap();
+    assert_eq!(compact.annotations("suede"), cmudict.annotations("suede"));
+
+    let options = RhymeOptions {
+        exclude: vec!["foreign".to_string(), "abbrev".to_string()],
+        ..RhymeOptions::default()
+    };
+    assert_eq!(compact.rhymes_for_with("fade", &options), vec!["braid"]);
+    assert!(!compact.rhyme_with("fade", "suede", &options).unwrap());
+}
+
+#[test]
+fn overlay_annotations() {
+    let mut cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    cmudict
+        .load_overlay_reader("+braid B R AE1 D # foreign\n-suede\nade EY1 D\n".as_bytes())
+        .unwrap();
+
+    assert_eq!(
+        cmudict.annotations("braid"),
+        Some(vec![None, Some("foreign".to_string())])
+    );
+    assert_eq!(cmudict.annotations("suede"), None);
+    assert_eq!(cmudict.annotations("ade"), Some(vec![None]));
+}
+
+#[test]
+fn homophones() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(cmudict.homophones("pair"), vec!["pare", "pear"]);
+    assert_eq!(cmudict.homophones(" Pear"), vec!["pair", "pare"]);
+    // on any pronunciation
+    assert_eq!(cmudict.homophones("read"), vec!["red"]);
+    assert_eq!(cmudict.homophones("red"), vec!["read"]);
+    assert!(cmudict.homophones("far").is_empty());
+    assert!(cmudict.homophones("zebra").is_empty());
+}
+
+#[test]
+fn homophones_ignoring_stress() {
+    let mut cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert!(cmudict.homophones("insight").is_empty());
+
+    let options = HomophoneOptions {
+        ignore_stress: true,
+    };
+    assert_eq!(cmudict.homophones_with("insight", &options), vec!["incite"]);
+    assert_eq!(
+        cmudict.homophones_with("pair", &options),
+        vec!["pare", "pear"]
+    );
+
+    // the index follows changes to the dictionary
+    cmudict.remove("incite");
+    assert!(cmudict.homophones_with("insight", &options).is_empty());
+    cmudict
+        .load_overlay_reader("pehr P EH1 R\n".as_bytes())
+        .unwrap();
+    assert_eq!(cmudict.homophones("pair"), vec!["pare", "pear", "pehr"]);
+}
+
+#[test]
+fn phonetic_distance() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(cmudict.phonetic_distance("far", "Far ").unwrap(), Some(0.0));
+    assert_eq!(cmudict.phonetic_distance("far", "tar").unwrap(), Some(0.8));
+    assert_eq!(
+        cmudict.phonetic_distance("miss", "mist").unwrap(),
+        Some(1.0)
+    );
+    // "cup" and "cut" differ by place only, "cup" and "car" by a lot more
+    assert!(
+        cmudict.phonetic_distance("cup", "cut").unwrap()
+            < cmudict.phonetic_distance("cup", "car").unwrap()
+    );
+    // the closest pronunciations of "read" are compared
+    assert_eq!(cmudict.phonetic_distance("read", "red").unwrap(), Some(0.0));
+    assert_eq!(
+        cmudict.phonetic_distance("read", "bead").unwrap(),
+        Some(0.8)
+    );
+    assert_eq!(cmudict.phonetic_distance("far", "zebra").unwrap(), None);
+}
+
+#[test]
+fn phonetic_similarity() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.phonetic_similarity("pair", "pear").unwrap(),
+        Some(1.0)
+    );
+    assert_eq!(
+        cmudict.phonetic_similarity("miss", "mist").unwrap(),
+        Some(0.75)
+    );
+    let similarity = cmudict
+        .phonetic_similarity("shopping", "cart")
+        .unwrap()
+        .unwrap();
+    assert!(similarity > 0.0 && similarity < 0.5);
+    assert_eq!(cmudict.phonetic_similarity("zebra", "far").unwrap(), None);
+}
+
+#[test]
+fn query() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    let words = |query: Query| cmudict.query(&query).unwrap();
+
+    assert_eq!(
+        words(Query {
+            stress: Some("01".to_string()),
+            rhymes_with: Some("Far ".to_string()),
+            ..Query::default()
+        }),
+        vec!["guitar"]
+    );
+    assert_eq!(
+        words(Query {
+            syllables: Some(2),
+            ends_with: vec!["IH0".to_string(), "NG".to_string()],
+            ..Query::default()
+        }),
+        vec!["bouncing", "bounding", "shopping"]
+    );
+    assert_eq!(
+        words(Query {
+            starts_with: vec!["b".to_string(), "aw".to_string()],
+            ..Query::default()
+        }),
+        vec!["bouncing", "bounding"]
+    );
+    assert_eq!(
+        words(Query {
+            stress: Some("*2*".to_string()),
+            ..Query::default()
+        }),
+        vec!["insight", "permeability"]
+    );
+    // any pronunciation can match
+    assert_eq!(
+        words(Query {
+            starts_with: vec!["HH".to_string(), "ER1".to_string()],
+            ..Query::default()
+        }),
+        vec!["herb"]
+    );
+    assert_eq!(
+        words(Query {
+            rhymes_with: Some("zebra".to_string()),
+            ..Query::default()
+        }),
+        Vec::<&str>::new()
+    );
+    assert_eq!(words(Query::default()).len(), 54);
+}
+
+#[test]
+fn query_invalid() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert!(cmudict
+        .query(&Query {
+            stress: Some("0x".to_string()),
+            ..Query::default()
+        })
+        .is_err());
+    assert!(cmudict
+        .query(&Query {
+            ends_with: vec!["XX".to_string()],
+            ..Query::default()
+        })
+        .is_err());
+}
+
+#[test]
+fn query_after_insert() {
+    let mut cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    let query = Query {
+        stress: Some("?1?".to_string()),
+        ..Query::default()
+    };
+    assert!(cmudict.query(&query).unwrap().is_empty());
+
+    cmudict
+        .insert(
+            "banana",
+            vec![vec!["B", "AH0", "N", "AE1", "N", "AH0"]
+                .into_iter()
+                .map(String::from)
+                .collect()],
+        )
+        .unwrap();
+    assert_eq!(cmudict.query(&query).unwrap(), vec!["banana"]);
+}
+
+#[test]
+fn introspection() {
+    let mut cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(cmudict.len(), 54);
+    assert!(!cmudict.is_empty());
+    assert_eq!(cmudict.iter().count(), 54);
+    assert_eq!(cmudict.words()[..3], ["a", "actor", "ade"]);
+    assert!(cmudict.contains("Read"));
+    assert!(!cmudict.contains("zaphod"));
+
+    cmudict.load_overlay("tests/fixtures/overlay.dict").unwrap();
+    assert_eq!(cmudict.len(), 55);
+    assert_eq!(cmudict.iter().count(), 55);
+    assert!(cmudict.contains("zaphod"));
+    assert!(!cmudict.contains("dog"));
+    assert!(!cmudict.words().contains(&"dog"));
+
+    let compact = CmuDict::from_compact_bytes(cmudict.to_compact().unwrap()).unwrap();
+    assert_eq!(compact.len(), 55);
+    assert_eq!(compact.words(), cmudict.words());
+}
+
+#[test]
+fn dict_text_round_trip() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    assert_eq!(
+        cmudict.to_dict_text(|_, _| true),
+        fs::read_to_string("tests/fixtures/cmudict.dict").unwrap()
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("subset.dict");
+    let path = path.to_str().unwrap();
+    cmudict
+        .write_dict(path, |word, _| word.starts_with('s'))
+        .unwrap();
+
+    let subset = CmuDict::from_dict_file(path).unwrap();
+    assert_eq!(
+        subset.words(),
+        vec!["sails", "say", "shopping", "silver", "snails", "snappy", "stampede", "star", "suede"]
+    );
+    assert_eq!(subset.annotations("suede"), cmudict.annotations("suede"));
+}
+
+#[test]
+fn json_subset() {
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("subset.json");
+    let path = path.to_str().unwrap();
+    cmudict
+        .write_json(path, |_, pronunciations| pronunciations.len() > 1)
+        .unwrap();
+
+    // loads verified against the sidecar, without downloading anything
+    let subset = CmuDict::new(path).unwrap();
+    assert_eq!(subset.words(), vec!["a", "herb", "read"]);
+    assert_eq!(subset.encoding("read"), cmudict.encoding("read"));
+    assert_eq!(subset.metadata().unwrap().entries, 3);
+    // not fetched from anywhere, so there's no upstream to pin
+    assert_eq!(subset.metadata().unwrap().upstream_sha256, None);
+    assert!(!fs::read_to_string(format!("{}.meta", path))
+        .unwrap()
+        .contains("upstream_sha256"));
+    assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        cmudict
+            .to_json(|_, pronunciations| pronunciations.len() > 1)
+            .unwrap()
+    );
+}
+
+#[test]
+fn phonetic_encoder() {
+    use ttaw::encoder::PhoneticEncoder;
+
+    let cmudict = CmuDict::from_dict_file("tests/fixtures/cmudict.dict").unwrap();
+    let encoder: &dyn PhoneticEncoder = &cmudict;
+
+    assert_eq!(
+        encoder.encode("read").unwrap(),
+        Some(vec!["R EH1 D".to_string(), "R IY1 D".to_string()])
+    );
+    assert_eq!(encoder.encode("zebra").unwrap(), None);
+    assert!(encoder.rhyme("far", "tar").unwrap());
+    assert!(encoder.alliterate("bouncing", "bears").unwrap());
+    assert_eq!(encoder.similarity("pair", "pear").unwrap(), Some(1.0));
+    assert_eq!(
+        encoder.similarity("miss", "mist").unwrap(),
+        cmudict.phonetic_similarity("miss", "mist").unwrap()
+    );
+}
@@ -0,0 +1,74 @@
+extern crate ttaw;
+
+use ttaw::encoder::PhoneticEncoder;
+use ttaw::mra::{compare, encoding, rating, MatchRating, Rating};
+
+// The worked examples from the Match Rating Approach's Wikipedia article.
+#[test]
+fn reference() {
+    for (a, b, codex_a, codex_b, similarity, minimum) in &[
+        ("Byrne", "Boern", "BYRN", "BRN", 5, 4),
+        ("Smith", "Smyth", "SMTH", "SMYTH", 5, 3),
+        ("Catherine", "Kathryn", "CTHRN", "KTHRYN", 4, 3),
+    ] {
+        assert_eq!(encoding(a), *codex_a);
+        assert_eq!(encoding(b), *codex_b);
+        assert_eq!(
+            rating(a, b),
+            Some(Rating {
+                similarity: *similarity,
+                minimum: *minimum
+            })
+        );
+        assert!(compare(a, b));
+    }
+}
+
+#[test]
+fn codex_first_and_last_three() {
+    assert_eq!(encoding("Schwarzenegger"), "SCHNGR");
+    assert_eq!(encoding("Abbotsford"), "ABTFRD");
+}
+
+#[test]
+fn no_match() {
+    assert!(!compare("Smith", "Jones"));
+    // too far apart in length to rate
+    assert_eq!(rating("Al", "Abbotsford"), None);
+    assert!(!compare("Al", "Abbotsford"));
+    assert!(!compare("", ""));
+    // the same name always matches
+    assert!(compare("Al", "AL"));
+}
+
+#[test]
+fn encoder() {
+    assert_eq!(
+        MatchRating.encode("Byrne").unwrap(),
+        Some(vec!["BYRN".to_string()])
+    );
+    assert_eq!(MatchRating.encode("").unwrap(), None);
+    assert_eq!(
+        MatchRating.similarity("Byrne", "Boern").unwrap(),
+        Some(5.0 / 6.0)
+    );
+    assert_eq!(
+        MatchRating.similarity("Al", "Abbotsford").unwrap(),
+        Some(0.0)
+    );
+    assert!(MatchRating.alliterate("Catherine", "Cathy").unwrap());
+    assert!(MatchRating.rhyme("Byrne", "Wyrne").unwrap());
+    // single-letter codes have no tail to compare
+    assert_eq!(encoding("Tata"), "T");
+    assert_eq!(encoding("Papa"), "P");
+    assert!(!MatchRating.rhyme("Tata", "Papa").unwrap());
+}
+
+// Polish and German letters code like the letters they're built on.
+#[test]
+fn diacritics() {
+    assert_eq!(encoding("Łukasiewicz"), "LKSWCZ");
+    assert_eq!(encoding("Żółkiewski"), "ZLKWSK");
+    assert_eq!(encoding("Strauß"), "STRS");
+    assert!(compare("Müller", "Muller"));
+}
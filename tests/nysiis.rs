@@ -0,0 +1,62 @@
+extern crate ttaw;
+
+use ttaw::encoder::PhoneticEncoder;
+use ttaw::nysiis::{encoding, Nysiis};
+
+#[test]
+fn reference() {
+    let full = Nysiis { length: None };
+    for (name, key) in &[
+        ("Bishop", "BASAP"),
+        ("Carlson", "CARLSAN"),
+        ("Franklin", "FRANCLAN"),
+        ("Greene", "GRAN"),
+        ("Hoffman", "HAFNAN"),
+        ("Knight", "NAGT"),
+        ("Larson", "LARSAN"),
+        ("Lynch", "LYNC"),
+        ("Mackenzie", "MCANSY"),
+        ("McDaniel", "MCDANAL"),
+        ("Richards", "RACARD"),
+        ("Watkins", "WATCAN"),
+        ("Wheeler", "WALAR"),
+    ] {
+        assert_eq!(full.code(name), *key, "{}", name);
+    }
+}
+
+#[test]
+fn truncated() {
+    assert_eq!(encoding("Carlson"), "CARLSA");
+    assert_eq!(encoding("McDaniel"), "MCDANA");
+    assert_eq!(encoding("Bishop"), "BASAP");
+    assert_eq!(Nysiis { length: Some(3) }.code("Bishop"), "BAS");
+}
+
+#[test]
+fn case_and_punctuation() {
+    assert_eq!(encoding("o'brien"), encoding("OBRIEN"));
+    assert_eq!(encoding(""), "");
+    assert_eq!(encoding("'"), "");
+}
+
+#[test]
+fn encoder() {
+    let nysiis = Nysiis::default();
+    assert_eq!(
+        nysiis.encode("Knight").unwrap(),
+        Some(vec!["NAGT".to_string()])
+    );
+    assert_eq!(nysiis.encode("").unwrap(), None);
+    assert!(nysiis.alliterate("Carlson", "Carr").unwrap());
+    assert_eq!(nysiis.similarity("Larson", "Carlson").unwrap(), Some(0.5));
+}
+
+// Polish and German letters code like the letters they're built on.
+#[test]
+fn diacritics() {
+    assert_eq!(encoding("Łukasiewicz"), "LACASA");
+    assert_eq!(encoding("Wałęsa"), encoding("Walesa"));
+    assert_eq!(encoding("Müller"), "MALAR");
+    assert_eq!(encoding("Schröder"), encoding("Schroder"));
+}
@@ -0,0 +1,52 @@
+extern crate ttaw;
+
+use ttaw::pronouncing::PronouncingDict;
+
+#[test]
+fn librispeech_lexicon() {
+    let layout = PronouncingDict {
+        tab_separated: true,
+        ..PronouncingDict::default()
+    };
+    let dict = layout
+        .load("tests/fixtures/librispeech-lexicon.txt")
+        .unwrap();
+
+    assert_eq!(
+        dict.encoding("far").unwrap(),
+        Some(vec![vec![
+            "F".to_string(),
+            "AA1".to_string(),
+            "R".to_string()
+        ]])
+    );
+    assert!(dict.rhyme("far", "tar").unwrap());
+    assert!(dict.alliteration("bouncing", "bears").unwrap());
+    assert!(!dict.alliteration("lazy", "dog").unwrap());
+}
+
+#[test]
+fn mfa_dictionary() {
+    let layout = PronouncingDict {
+        tab_separated: true,
+        probabilities: true,
+        syllable_markers: vec!['.'],
+    };
+    let dict = layout.load("tests/fixtures/mfa.dict").unwrap();
+
+    assert!(dict.alliteration("bouncing", "bears").unwrap());
+    assert_eq!(dict.syllables("tomato").unwrap(), Some(vec![3, 3]));
+    // the likelier "tomayto" comes first
+    assert_eq!(dict.rhymes_for("tomato"), vec!["potato"]);
+}
+
+#[test]
+fn wrong_layout() {
+    // the probabilities aren't phones
+    assert!(PronouncingDict::default()
+        .load("tests/fixtures/mfa.dict")
+        .is_err());
+    assert!(PronouncingDict::default()
+        .load("tests/fixtures/missing.dict")
+        .is_err());
+}
@@ -0,0 +1,159 @@
+extern crate ttaw;
+
+use ttaw::encoder::PhoneticEncoder;
+use ttaw::soundex::{
+    daitch_mokotoff, refined_soundex, soundex, DaitchMokotoff, RefinedSoundex, Soundex,
+};
+
+// Examples from the US National Archives' description of the census coding.
+#[test]
+fn american_reference() {
+    for (name, code) in &[
+        ("Robert", "R163"),
+        ("Rupert", "R163"),
+        ("Rubin", "R150"),
+        ("Ashcraft", "A261"),
+        ("Ashcroft", "A261"),
+        ("Tymczak", "T522"),
+        ("Pfister", "P236"),
+        ("Honeyman", "H555"),
+        ("Washington", "W252"),
+        ("Lee", "L000"),
+        ("Gutierrez", "G362"),
+        ("Jackson", "J250"),
+    ] {
+        assert_eq!(soundex(name), *code, "{}", name);
+    }
+}
+
+#[test]
+fn american_case_and_punctuation() {
+    assert_eq!(soundex("o'hara"), soundex("OHARA"));
+    assert_eq!(soundex(" Lloyd "), "L300");
+}
+
+#[test]
+fn american_length() {
+    let long = Soundex { length: 8 };
+    assert_eq!(long.code("Washington"), "W2523500");
+    assert_eq!(long.code("Lee"), "L0000000");
+
+    let short = Soundex { length: 2 };
+    assert_eq!(short.code("Washington"), "W2");
+}
+
+// Examples from the Apache Commons Codec documentation and tests.
+#[test]
+fn refined_reference() {
+    for (word, code) in &[
+        ("testing", "T6036084"),
+        ("TESTING", "T6036084"),
+        ("The", "T60"),
+        ("quick", "Q503"),
+        ("brown", "B1908"),
+        ("fox", "F205"),
+        ("jumped", "J408106"),
+        ("lazy", "L7050"),
+        ("dogs", "D6043"),
+        ("Braz", "B1905"),
+    ] {
+        assert_eq!(refined_soundex(word), *code, "{}", word);
+    }
+}
+
+#[test]
+fn refined_length() {
+    let short = RefinedSoundex { length: Some(3) };
+    assert_eq!(short.code("jumped"), "J40");
+    assert_eq!(short.code("Q"), "Q5");
+}
+
+#[test]
+fn encoders() {
+    let soundex = Soundex::default();
+    assert_eq!(
+        soundex.encode("Robert").unwrap(),
+        Some(vec!["R163".to_string()])
+    );
+    assert!(soundex.alliterate("Robert", "Rupert").unwrap());
+    assert!(soundex.rhyme("far", "tar").unwrap());
+    assert_eq!(soundex.similarity("Robert", "Rupert").unwrap(), Some(1.0));
+
+    let refined = RefinedSoundex::default();
+    assert!(!refined.rhyme("brown", "fox").unwrap());
+    assert_eq!(refined.encode("").unwrap(), None);
+}
+
+// Examples from Gary Mokotoff's description of the system and the
+// Daitch–Mokotoff Wikipedia article.
+#[test]
+fn daitch_mokotoff_reference() {
+    for (name, codes) in &[
+        ("Moskowitz", vec!["645740"]),
+        ("Moskovitz", vec!["645740"]),
+        ("Auerbach", vec!["097500", "097400"]),
+        ("Ohrbach", vec!["097500", "097400"]),
+        ("Lipshitz", vec!["874400"]),
+        ("Peters", vec!["739400", "734000"]),
+        ("Schwarzenegger", vec!["479465", "474659"]),
+    ] {
+        assert_eq!(&daitch_mokotoff(name), codes, "{}", name);
+    }
+}
+
+#[test]
+fn daitch_mokotoff_branches() {
+    // "C" and "CK" both branch, "CK" into a one and a two digit code
+    assert_eq!(
+        daitch_mokotoff("Cackowski"),
+        vec!["557450", "545745", "457450", "445745"]
+    );
+    // letter groups starting a name can code differently
+    assert_eq!(daitch_mokotoff("Shtern"), vec!["296000"]);
+    // the same code twice in a row counts once, unless M and N meet
+    assert_eq!(daitch_mokotoff("Otto"), vec!["030000"]);
+    assert_eq!(daitch_mokotoff("Amn"), vec!["066000"]);
+    assert!(daitch_mokotoff("").is_empty());
+}
+
+#[test]
+fn daitch_mokotoff_long_branching_names() {
+    // branches stop at six digits, so a long name full of branching letters
+    // doesn't double the work at every letter
+    let codes = daitch_mokotoff(&"CKJ".repeat(10));
+    assert_eq!(codes.len(), 37);
+    assert_eq!(codes.first().unwrap(), "555555");
+    assert_eq!(codes.last().unwrap(), "454454");
+    assert!(codes.iter().all(|code| code.len() == 6));
+    assert_eq!(daitch_mokotoff(&"CKJ".repeat(1000)), codes);
+}
+
+#[test]
+fn daitch_mokotoff_encoder() {
+    let encoder = DaitchMokotoff;
+    assert_eq!(
+        encoder.encode("Auerbach").unwrap(),
+        Some(vec!["097500".to_string(), "097400".to_string()])
+    );
+    assert_eq!(encoder.encode("").unwrap(), None);
+    assert!(encoder.alliterate("Auerbach", "Ohrbach").unwrap());
+    assert_eq!(
+        encoder.similarity("Auerbach", "Ohrbach").unwrap(),
+        Some(1.0)
+    );
+    assert!(encoder.similarity("Moskowitz", "Peters").unwrap().unwrap() < 0.5);
+}
+
+// Polish and German letters code like the letters they're built on.
+#[test]
+fn diacritics() {
+    assert_eq!(soundex("Łukasiewicz"), "L222");
+    assert_eq!(soundex("Żółkiewski"), soundex("Zolkiewski"));
+    assert_eq!(soundex("Müller"), "M460");
+    assert_eq!(soundex("Strauß"), soundex("Strauss"));
+    assert_eq!(refined_soundex("Wałęsa"), refined_soundex("Walesa"));
+    assert_eq!(daitch_mokotoff("Łukasiewicz"), vec!["854740"]);
+    assert_eq!(daitch_mokotoff("Żółkiewski"), vec!["485745"]);
+    assert_eq!(daitch_mokotoff("Schröder"), vec!["493900"]);
+    assert_eq!(daitch_mokotoff("Weiß"), daitch_mokotoff("Weiss"));
+}
@@ -0,0 +1,279 @@
+extern crate tempfile;
+extern crate ttaw;
+
+use std::fs;
+#[cfg(feature = "reqwest")]
+use std::io::{Read, Write};
+#[cfg(feature = "reqwest")]
+use std::net::TcpListener;
+#[cfg(feature = "reqwest")]
+use std::thread;
+use ttaw::cmu::CmuDict;
+#[cfg(feature = "reqwest")]
+use ttaw::source::HttpSource;
+use ttaw::source::{BytesSource, DictionarySource, FileSource};
+use ttaw::Error;
+
+const DICT: &str = "far F AA1 R\ntar T AA1 R\nbears B EH1 R Z\nbouncing B AW1 N S IH0 NG\n";
+
+// Serve `body` for a single request on a local port and return its URL.
+#[cfg(feature = "reqwest")]
+fn serve_once(status: &'static str, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 1024];
+        let _ = stream.read(&mut request).unwrap();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+        .unwrap();
+    });
+
+    format!("http://{}/cmudict.dict", addr)
+}
+
+#[cfg(feature = "reqwest")]
+#[test]
+fn http_source() {
+    let source = HttpSource::new(&serve_once("200 OK", DICT));
+    let cmudict = CmuDict::from_source(&source).unwrap();
+    assert!(cmudict.rhyme("far", "tar").unwrap());
+    assert!(cmudict.alliteration("bouncing", "bears").unwrap());
+}
+
+#[cfg(feature = "reqwest")]
+#[test]
+fn http_source_not_found() {
+    let source = HttpSource::new(&serve_once("404 Not Found", "nope"));
+    assert!(CmuDict::from_source(&source).is_err());
+}
+
+#[cfg(feature = "reqwest")]
+#[test]
+fn http_source_default_url() {
+    assert_eq!(HttpSource::default().url(), ttaw::source::CMUDICT_URL);
+}
+
+#[test]
+fn bytes_source() {
+    let source = BytesSource::new(DICT.as_bytes().to_vec());
+    assert_eq!(source.fetch(), Ok(DICT.as_bytes().to_vec()));
+
+    let cmudict = CmuDict::from_source(&source).unwrap();
+    assert!(cmudict.rhyme("far", "tar").unwrap());
+}
+
+#[test]
+fn file_source() {
+    let source = FileSource::new("tests/fixtures/cmudict.dict");
+    let cmudict = CmuDict::from_source(&source).unwrap();
+    assert!(cmudict.rhyme("a", "say").unwrap());
+
+    let missing = FileSource::new("tests/fixtures/does-not-exist.dict");
+    assert!(CmuDict::from_source(&missing).is_err());
+}
+
+#[test]
+fn closure_source() {
+    let source = || Ok(DICT.as_bytes().to_vec());
+    let cmudict = CmuDict::from_source(&source).unwrap();
+    assert!(cmudict.rhyme("far", "tar").unwrap());
+
+    let failing = || Err(Error::InputError("offline".to_string()));
+    assert_eq!(
+        CmuDict::from_source(&failing).err(),
+        Some(Error::InputError("offline".to_string()))
+    );
+}
+
+#[test]
+fn with_source_serializes_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.json");
+    let path = path.to_str().unwrap();
+
+    let cmudict = CmuDict::with_source(path, &BytesSource::new(DICT.as_bytes().to_vec())).unwrap();
+    assert!(cmudict.rhyme("far", "tar").unwrap());
+    assert!(fs::metadata(path).is_ok());
+
+    // the serialized copy is used from here on, the source isn't consulted
+    let failing = || Err(Error::InputError("offline".to_string()));
+    let cmudict = CmuDict::with_source(path, &failing).unwrap();
+    assert!(cmudict.rhyme("far", "tar").unwrap());
+}
+
+#[test]
+fn with_source_directory() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let cmudict = CmuDict::with_source(
+        dir.path().to_str().unwrap(),
+        &BytesSource::new(DICT.as_bytes().to_vec()),
+    )
+    .unwrap();
+    assert!(cmudict.rhyme("far", "tar").unwrap());
+    assert!(dir.path().join("cmudict.json").exists());
+}
+
+#[test]
+fn serialized_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.json");
+
+    let cmudict = CmuDict::with_source(
+        path.to_str().unwrap(),
+        &FileSource::new("tests/fixtures/cmudict.dict"),
+    )
+    .unwrap();
+
+    let metadata = cmudict.metadata().unwrap();
+    assert_eq!(
+        metadata.source,
+        Some("tests/fixtures/cmudict.dict".to_string())
+    );
+    assert_eq!(metadata.sha256.len(), 64);
+    assert_eq!(metadata.upstream_sha256.as_ref().map(String::len), Some(64));
+    assert!(dir.path().join("cmudict.json.meta").exists());
+
+    // loading the serialized copy again reports the same provenance
+    let reloaded = CmuDict::new(path.to_str().unwrap()).unwrap();
+    assert_eq!(reloaded.metadata(), Some(metadata));
+
+    // annotations are kept alongside it
+    assert_eq!(
+        reloaded.annotations("ade"),
+        Some(vec![Some("abbrev".to_string())])
+    );
+}
+
+#[test]
+fn tampered_dictionary() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.json");
+    let path = path.to_str().unwrap();
+
+    CmuDict::with_source(path, &BytesSource::new(DICT.as_bytes().to_vec())).unwrap();
+
+    let tampered = fs::read_to_string(path).unwrap().replace("AA1", "AO1");
+    fs::write(path, tampered).unwrap();
+
+    match CmuDict::new(path) {
+        Err(Error::IntegrityError(_)) => {}
+        _ => panic!("expected an integrity error"),
+    }
+}
+
+#[test]
+fn truncated_dictionary() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.json");
+    let path = path.to_str().unwrap();
+
+    CmuDict::with_source(path, &BytesSource::new(DICT.as_bytes().to_vec())).unwrap();
+
+    let json = fs::read(path).unwrap();
+    fs::write(path, &json[..json.len() / 2]).unwrap();
+
+    match CmuDict::new(path) {
+        Err(Error::IntegrityError(_)) => {}
+        _ => panic!("expected an integrity error"),
+    }
+}
+
+#[test]
+fn mismatched_entry_count() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.json");
+    let meta_path = dir.path().join("cmudict.json.meta");
+
+    CmuDict::with_source(
+        path.to_str().unwrap(),
+        &BytesSource::new(DICT.as_bytes().to_vec()),
+    )
+    .unwrap();
+
+    let meta = fs::read_to_string(&meta_path)
+        .unwrap()
+        .replace("\"entries\":4", "\"entries\":5");
+    fs::write(&meta_path, meta).unwrap();
+
+    match CmuDict::new(path.to_str().unwrap()) {
+        Err(Error::IntegrityError(_)) => {}
+        _ => panic!("expected an integrity error"),
+    }
+}
+
+#[test]
+fn missing_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.json");
+
+    CmuDict::with_source(
+        path.to_str().unwrap(),
+        &BytesSource::new(DICT.as_bytes().to_vec()),
+    )
+    .unwrap();
+    fs::remove_file(dir.path().join("cmudict.json.meta")).unwrap();
+
+    // without the sidecar there's nothing to verify the file against, so it's
+    // regenerated rather than loaded
+    let failing = || Err(Error::InputError("offline".to_string()));
+    match CmuDict::with_source(path.to_str().unwrap(), &failing) {
+        Err(Error::InputError(_)) => {}
+        _ => panic!("expected the dictionary to be fetched again"),
+    }
+
+    let cmudict = CmuDict::with_source(
+        path.to_str().unwrap(),
+        &BytesSource::new("far F AA1 R\nstar S T AA1 R\n".as_bytes().to_vec()),
+    )
+    .unwrap();
+    assert_eq!(cmudict.metadata().unwrap().entries, 2);
+    assert!(cmudict.rhyme("far", "star").unwrap());
+    assert!(dir.path().join("cmudict.json.meta").exists());
+}
+
+#[test]
+fn truncated_dictionary_with_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.json");
+    let path = path.to_str().unwrap();
+    let source = BytesSource::new(DICT.as_bytes().to_vec());
+
+    CmuDict::with_source(path, &source).unwrap();
+
+    // as if interrupted while writing, the sidecar is already in place
+    let json = fs::read(path).unwrap();
+    fs::write(path, &json[..json.len() / 2]).unwrap();
+
+    match CmuDict::with_source(path, &source) {
+        Err(Error::IntegrityError(_)) => {}
+        _ => panic!("expected an integrity error"),
+    }
+}
+
+#[test]
+fn no_temporary_files_left() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cmudict.json");
+
+    CmuDict::with_source(
+        path.to_str().unwrap(),
+        &BytesSource::new(DICT.as_bytes().to_vec()),
+    )
+    .unwrap();
+
+    let mut files = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<String>>();
+    files.sort();
+    assert_eq!(files, vec!["cmudict.json", "cmudict.json.meta"]);
+}